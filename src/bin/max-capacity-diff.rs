@@ -0,0 +1,85 @@
+//! Compares two reports saved by `Report::write_to_file`, exiting with a
+//! non-zero status if any named collection regressed past the threshold.
+//!
+//! ```text
+//! max-capacity-diff <baseline.json> <current.json> [--threshold <percent>]
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use max_capacity::diff::ReportDiff;
+use max_capacity::Report;
+
+const USAGE: &str =
+    "usage: max-capacity-diff <baseline.json> <current.json> [--threshold <percent>]";
+
+struct Args {
+    baseline: PathBuf,
+    current: PathBuf,
+    threshold_percent: f64,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut paths = Vec::new();
+    let mut threshold_percent = 0.;
+    let mut args = env::args_os().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--threshold" {
+            threshold_percent = args
+                .next()
+                .and_then(|value| value.to_str()?.parse().ok())
+                .ok_or("--threshold expects a percentage")?;
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    match <[PathBuf; 2]>::try_from(paths) {
+        Ok([baseline, current]) => Ok(Args {
+            baseline,
+            current,
+            threshold_percent,
+        }),
+        Err(_) => Err("expected a baseline and a current report".into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let load = |path: &PathBuf| {
+        Report::read_from_file(path).map_err(|err| eprintln!("{}: {err}", path.display()))
+    };
+    let (Ok(baseline), Ok(current)) = (load(&args.baseline), load(&args.current)) else {
+        return ExitCode::from(2);
+    };
+    let diff = ReportDiff::new(&baseline, &current);
+
+    print!("{diff}");
+
+    let regressions: Vec<_> = diff.regressions(args.threshold_percent).collect();
+
+    if regressions.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+
+    eprintln!(
+        "{} collection(s) regressed past {}%:",
+        regressions.len(),
+        args.threshold_percent
+    );
+
+    for change in regressions {
+        eprintln!("{change}");
+    }
+
+    ExitCode::FAILURE
+}
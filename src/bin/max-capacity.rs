@@ -0,0 +1,122 @@
+//! Renders and queries a report saved by `Report::write_to_file`, or streamed
+//! by a `JsonLinesSink`.
+//!
+//! ```text
+//! max-capacity <report> [--name <name>] [--violations-only] [--min-reallocs <n>]
+//!              [--sort uid|name|peak|reallocs] [--summary | --totals]
+//!              [--format table|markdown|html|json]
+//! ```
+//!
+//! The summary only applies to the `table` format.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use max_capacity::{LineItem, PrintOptions, Report, ReportFormat, SortBy};
+
+const USAGE: &str = "usage: max-capacity <report> [--name <name>] [--violations-only] \
+                     [--min-reallocs <n>] [--sort uid|name|peak|reallocs] [--summary | --totals] \
+                     [--format table|markdown|html|json]";
+
+#[derive(Default)]
+struct Args {
+    path: PathBuf,
+    options: PrintOptions,
+    totals: bool,
+    format: Option<ReportFormat>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut parsed = Args::default();
+    let mut path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} expects a value"));
+
+        match arg.as_str() {
+            "--name" => parsed.options = parsed.options.name_contains(&value()?),
+            "--violations-only" => parsed.options = parsed.options.violations_only(true),
+            "--min-reallocs" => {
+                let min = value()?
+                    .parse()
+                    .map_err(|_| "--min-reallocs expects a number")?;
+
+                parsed.options = parsed.options.min_reallocations(min);
+            }
+            "--sort" => {
+                let sort_by = match value()?.as_str() {
+                    "uid" => SortBy::Uid,
+                    "name" => SortBy::Name,
+                    "peak" => SortBy::PeakLen,
+                    "reallocs" => SortBy::Reallocations,
+                    sort => return Err(format!("unknown sort `{sort}`")),
+                };
+
+                parsed.options = parsed.options.sort_by(sort_by);
+            }
+            "--summary" => parsed.options = parsed.options.summary(true),
+            "--totals" => parsed.totals = true,
+            "--format" => {
+                let format = value()?;
+
+                parsed.format = Some(
+                    format
+                        .parse()
+                        .map_err(|_| format!("unknown format `{format}`"))?,
+                );
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`")),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Err("expected a single report".into()),
+        }
+    }
+
+    parsed.path = path.ok_or("expected a report")?;
+
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let line_items = match Report::read_from_file(&args.path) {
+        Ok(line_items) => line_items,
+        Err(err) => {
+            eprintln!("{}: {err}", args.path.display());
+            return ExitCode::from(2);
+        }
+    };
+
+    match args.format {
+        _ if args.totals => (),
+        None | Some(ReportFormat::Table) => {
+            print!("{}", args.options.render(&line_items));
+            return ExitCode::SUCCESS;
+        }
+        Some(format) => {
+            print!("{}", format.render(&args.options.apply(line_items)));
+            return ExitCode::SUCCESS;
+        }
+    }
+
+    let line_items = args.options.apply(line_items);
+    let violating = line_items
+        .iter()
+        .filter(|line_item| !line_item.violations().is_empty())
+        .count();
+    let reallocations: usize = line_items.iter().map(LineItem::reallocations).sum();
+
+    println!(
+        "{} collection(s), {reallocations} reallocation(s), {violating} violating collection(s)",
+        line_items.len()
+    );
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,340 @@
+//! Budgets let capacity assumptions live in a reviewed file rather than being
+//! scattered across `with_capacity` calls. A budget file maps collection
+//! names to their max capacity, one per line:
+//!
+//! ```text
+//! # Comments and blank lines are ignored
+//! my_map = 128
+//! "http.requests" = 64
+//! "with \"quotes\" # and = signs" = 8
+//! ```
+//!
+//! Names which aren't made of letters, digits, `_` and `-` must be quoted,
+//! escaping `"` and `\` with a backslash.
+//!
+//! Once loaded, naming a collection via `set_name` or `with_name` sets its max
+//! capacity to the budgeted one. Named collections without a budget are
+//! reported as unbudgeted.
+//!
+//! A budget file can also be generated from an observed run with
+//! `Report::export_budgets`, to be enforced in later runs. It budgets the peak
+//! capacity rather than the peak length, as that's what budgets are checked
+//! against and capacities grow in steps.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use compact_str::CompactString;
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+
+use crate::{Error, LineItem, Report};
+
+static BUDGETS: OnceCell<DashMap<CompactString, usize>> = OnceCell::new();
+
+/// The budgeted capacity for `name`, if any.
+pub(crate) fn lookup(name: &str) -> Option<usize> {
+    BUDGETS.get()?.get(name).map(|budget| *budget)
+}
+
+/// Whether `name` should be reported as unbudgeted; only true once budgets
+/// have been provided.
+pub(crate) fn is_unbudgeted(name: &str) -> bool {
+    BUDGETS
+        .get()
+        .is_some_and(|budgets| !budgets.contains_key(name))
+}
+
+fn parse(contents: &str) -> Result<Vec<(CompactString, usize)>, Error> {
+    let mut budgets = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let malformed = |reason: &str| Error::MalformedBudgetFile {
+            line: i + 1,
+            reason: reason.into(),
+        };
+        let line = line.trim_start();
+
+        // Quoted names may contain `#` and `=`, so they're read before
+        // stripping the comment
+        let (name, rest) = match line.strip_prefix('"') {
+            Some(quoted) => parse_quoted(quoted).map_err(malformed)?,
+            None => (CompactString::default(), line),
+        };
+        let rest = rest.split('#').next().unwrap_or_default().trim();
+
+        if name.is_empty() && rest.is_empty() {
+            continue;
+        }
+
+        let (bare_name, capacity) = rest
+            .split_once('=')
+            .ok_or_else(|| malformed("expected `name = capacity`"))?;
+        let name = match bare_name.trim() {
+            "" => name,
+            bare_name if name.is_empty() => bare_name.into(),
+            _ => return Err(malformed("expected `=` after the quoted name")),
+        };
+
+        if name.is_empty() {
+            return Err(malformed("missing collection name"));
+        }
+
+        let capacity = capacity
+            .trim()
+            .replace('_', "")
+            .parse()
+            .map_err(|_| malformed("capacity must be a non-negative integer"))?;
+
+        budgets.push((name, capacity));
+    }
+
+    Ok(budgets)
+}
+
+/// Reads a quoted name up to its closing quote, unescaping `\"` and `\\`,
+/// and returns it along with the rest of the line.
+fn parse_quoted(quoted: &str) -> Result<(CompactString, &str), &'static str> {
+    let mut name = CompactString::default();
+    let mut chars = quoted.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((name, &quoted[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => name.push(c),
+                _ => return Err("only `\\\"` and `\\\\` may be escaped in a name"),
+            },
+            c => name.push(c),
+        }
+    }
+
+    Err("missing closing quote")
+}
+
+fn render(line_items: &[LineItem], headroom_percent: u32) -> String {
+    // Collections sharing a name share a budget, so it must fit the largest
+    let mut peaks = BTreeMap::new();
+
+    for line_item in line_items.iter().filter(|line_item| line_item.is_named()) {
+        let peak = peaks.entry(line_item.instance_name.as_str()).or_insert(0);
+        *peak = line_item.peak_capacity().max(*peak);
+    }
+
+    let mut budgets = String::new();
+
+    for (name, peak) in peaks {
+        let is_bare = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        let budget = (peak as u128 * (100 + headroom_percent as u128)).div_ceil(100);
+
+        if is_bare {
+            let _ = writeln!(budgets, "{name} = {budget}");
+        } else {
+            let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = writeln!(budgets, "\"{name}\" = {budget}");
+        }
+    }
+
+    budgets
+}
+
+impl Report {
+    /// Loads a budget file, adding to (and overriding) any budgets already
+    /// loaded. See the [`budget`](crate::budget) module for the format.
+    pub fn load_budgets(path: &Path) -> Result<(), Error> {
+        Report::load_budgets_str(&fs::read_to_string(path)?)
+    }
+
+    /// Same as `load_budgets`, but reads the budgets from a string.
+    pub fn load_budgets_str(contents: &str) -> Result<(), Error> {
+        let table = BUDGETS.get_or_init(DashMap::new);
+
+        for (name, capacity) in parse(contents)? {
+            table.insert(name, capacity);
+        }
+
+        Ok(())
+    }
+
+    /// Budgets a single collection name.
+    pub fn set_budget(name: &str, capacity: usize) {
+        BUDGETS
+            .get_or_init(DashMap::new)
+            .insert(name.into(), capacity);
+    }
+
+    /// The budgeted capacity for `name`, if any.
+    pub fn budget(name: &str) -> Option<usize> {
+        lookup(name)
+    }
+
+    /// Writes the peak capacity observed for every named collection as a budget
+    /// file which can be loaded back with `load_budgets`. Each budget is grown
+    /// by `headroom_percent` (rounding up), which may be zero.
+    pub fn export_budgets(path: &Path, headroom_percent: u32) -> Result<(), Error> {
+        Ok(fs::write(path, Report::render_budgets(headroom_percent))?)
+    }
+
+    /// Same as `export_budgets`, but returns the budget file as a string.
+    pub fn render_budgets(headroom_percent: u32) -> String {
+        render(&Report::line_items(), headroom_percent)
+    }
+}
+
+#[test]
+fn test_parse() {
+    let budgets = parse("# header\n\nmy_map = 128 # trailing\n\"http.requests\"=1_024\n").unwrap();
+
+    assert_eq!(
+        budgets,
+        [("my_map".into(), 128), ("http.requests".into(), 1024)]
+    );
+    assert!(matches!(
+        parse("my_map = 1\nmy_vec 2"),
+        Err(Error::MalformedBudgetFile { line: 2, .. })
+    ));
+    assert!(matches!(
+        parse("my_map = -1"),
+        Err(Error::MalformedBudgetFile { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_budget_enforced_by_name() {
+    use crate::collections::HashMap;
+    use crate::ReportEvent;
+    use crate::Reportable;
+
+    Report::load_budgets_str("budget_test_map = 16").unwrap();
+
+    let map = HashMap::<u32, ()>::with_capacity(4).with_name("budget_test_map");
+    let unbudgeted = HashMap::<u32, ()>::new().with_name("budget_test_other");
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(4));
+    assert_eq!(line_item.events[1], ReportEvent::MaxCapacitySet(16));
+    assert_eq!(line_item.max_capacity(), Some(16));
+    assert!(!line_item.is_unbudgeted());
+    assert!(Report::get_line_item(&unbudgeted).is_unbudgeted());
+    assert!(Report::get(unbudgeted.id()).events.is_empty());
+}
+
+#[test]
+fn test_budget_below_capacity() {
+    use crate::vec::Vec;
+    use crate::{Limit, ReportEvent};
+
+    Report::set_budget("presized_test_vec", 16);
+
+    let mut vec = Vec::<u8>::with_capacity(100).with_name("presized_test_vec");
+
+    for i in 0..200 {
+        vec.push(i);
+    }
+
+    let line_item = Report::get_line_item(&vec);
+    let violations = line_item.violations();
+
+    assert_eq!(line_item.events[2], ReportEvent::CapacityExceeded(16, 100));
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].limit, Limit::Capacity);
+    assert_eq!(violations[0].max_capacity, 16);
+    assert_eq!(violations[0].capacity, vec.capacity());
+}
+
+#[test]
+fn test_render_round_trip() {
+    use crate::{ReportEvent, Uid};
+
+    let line_item = |name, capacity| {
+        crate::test_line_item(name, 0, &[ReportEvent::CapacityIncrease(0, capacity)])
+    };
+    let line_items = [
+        line_item("my_map", 10),
+        line_item("my_map", 30),
+        line_item("http.requests", 7),
+        line_item("a#b", 1),
+        line_item("x = \"y\\z\"", 2),
+        LineItem::new(Uid::new()),
+    ];
+    let rendered = render(&line_items, 10);
+
+    assert_eq!(
+        rendered,
+        "\"a#b\" = 2\n\"http.requests\" = 8\nmy_map = 33\n\"x = \\\"y\\\\z\\\"\" = 3\n"
+    );
+    assert_eq!(
+        parse(&rendered).unwrap(),
+        [
+            ("a#b".into(), 2),
+            ("http.requests".into(), 8),
+            ("my_map".into(), 33),
+            ("x = \"y\\z\"".into(), 3)
+        ]
+    );
+}
+
+#[test]
+fn test_exported_budgets_rerun() {
+    use crate::collections::HashMap;
+    use crate::vec::Vec;
+
+    fn workload() -> (Vec<u8>, HashMap<u32, ()>) {
+        let mut vec = Vec::new().with_name("rerun_test_vec");
+        let mut map = HashMap::new().with_name("rerun_test_map");
+
+        for i in 0..3 {
+            vec.push(i);
+        }
+
+        for i in 0..4 {
+            map.insert(i, ());
+        }
+
+        (vec, map)
+    }
+
+    let (vec, map) = workload();
+    let rendered: String = Report::render_budgets(0)
+        .lines()
+        .filter(|line| line.starts_with("rerun_test_"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+
+    // Budgets must cover the capacity reached, not just the length
+    assert_eq!(
+        rendered,
+        format!(
+            "rerun_test_map = {}\nrerun_test_vec = {}\n",
+            map.capacity(),
+            vec.capacity()
+        )
+    );
+
+    Report::load_budgets_str(&rendered).unwrap();
+
+    let (vec, map) = workload();
+
+    assert!(Report::check_collection(&vec).is_ok());
+    assert!(Report::check_collection(&map).is_ok());
+}
+
+#[test]
+fn test_peak_len_observed() {
+    use crate::vec::Vec;
+
+    let mut vec = Vec::with_capacity(8).with_name("peak_test_vec");
+
+    vec.push(0);
+    vec.push(1);
+    vec.push(2);
+    vec.pop();
+    vec.push(3);
+
+    assert_eq!(Report::get_line_item(&vec).peak_len, 3);
+    assert!(Report::render_budgets(0).contains("peak_test_vec = 8\n"));
+}
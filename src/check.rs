@@ -0,0 +1,274 @@
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use compact_str::CompactString;
+
+use crate::{Error, LineItem, Report, ReportEvent, Reportable, Uid};
+
+/// What a budget limits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Limit {
+    Capacity,
+    /// The length of a collection without a capacity, such as a `BTreeMap`.
+    Len,
+    /// The estimated memory held by a collection without a capacity.
+    Bytes,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Limit::Capacity => write!(f, "capacity"),
+            Limit::Len => write!(f, "len"),
+            Limit::Bytes => write!(f, "bytes"),
+        }
+    }
+}
+
+/// A collection which grew past the max capacity it was given.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    pub id: Uid,
+    pub instance_name: CompactString,
+    /// What `max_capacity` and `capacity` are measured in.
+    pub limit: Limit,
+    pub max_capacity: usize,
+    /// The largest capacity reached while `max_capacity` was in effect. Max
+    /// lengths and bytes are only reported once, so for those it's the value
+    /// they were first exceeded with.
+    pub capacity: usize,
+}
+
+impl Violation {
+    /// How far past its max capacity the collection grew.
+    pub fn excess(&self) -> usize {
+        self.capacity - self.max_capacity
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "- {} - {}: max {} {}",
+            self.id, self.instance_name, self.limit, self.max_capacity
+        )?;
+        write!(
+            f,
+            "+ {} - {}: {} {} (+{})",
+            self.id,
+            self.instance_name,
+            self.limit,
+            self.capacity,
+            self.excess()
+        )
+    }
+}
+
+impl From<Violation> for Error {
+    fn from(violation: Violation) -> Self {
+        Error::BudgetExceeded {
+            id: violation.id,
+            limit: violation.limit,
+            max_capacity: violation.max_capacity,
+            capacity: violation.capacity,
+        }
+    }
+}
+
+/// Every violation found by a check, ordered by `Uid`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Violations(pub Vec<Violation>);
+
+impl Violations {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Violation> {
+        self.0.iter()
+    }
+
+    fn into_result(self) -> Result<(), Violations> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for Violations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} capacity violation(s):", self.len())?;
+
+        for violation in self.iter() {
+            write!(f, "\n{violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StdError for Violations {}
+
+impl LineItem {
+    /// Finds every max capacity this line item grew past. A max capacity
+    /// stays in effect until the next `MaxCapacitySet` event replaces it.
+    /// Max lengths and bytes exceeded are violations of their own.
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut current: Option<Violation> = None;
+        let mut max_capacity = None;
+        let violation = |limit, max_capacity, capacity| Violation {
+            id: self.id,
+            instance_name: self.instance_name.clone(),
+            limit,
+            max_capacity,
+            capacity,
+        };
+
+        for record in &self.events {
+            match record.event {
+                ReportEvent::MaxCapacitySet(cap) => {
+                    violations.extend(current.take());
+                    max_capacity = Some(cap);
+                }
+                ReportEvent::CapacityIncrease(_, new_cap) | ReportEvent::Spilled(_, new_cap) => {
+                    match (max_capacity, &mut current) {
+                        (_, Some(violation)) => {
+                            violation.capacity = violation.capacity.max(new_cap)
+                        }
+                        (Some(max_capacity), None) if new_cap > max_capacity => {
+                            current = Some(violation(Limit::Capacity, max_capacity, new_cap))
+                        }
+                        _ => (),
+                    }
+                }
+                ReportEvent::CapacityExceeded(max_capacity, capacity) => {
+                    current = Some(violation(Limit::Capacity, max_capacity, capacity))
+                }
+                ReportEvent::LenExceeded(max_len, len) => {
+                    violations.push(violation(Limit::Len, max_len, len))
+                }
+                ReportEvent::BytesExceeded(max_bytes, bytes) => {
+                    violations.push(violation(Limit::Bytes, max_bytes, bytes))
+                }
+                ReportEvent::CapacityDecrease(..)
+                | ReportEvent::Annotation(_)
+                | ReportEvent::MaxBytesSet(_) => (),
+            }
+        }
+
+        violations.extend(current);
+        violations
+    }
+}
+
+impl Report {
+    /// Checks every line item in the report for capacity violations.
+    pub fn check() -> Result<(), Violations> {
+        Report::check_line_items(&Report::line_items())
+    }
+
+    /// Checks a single collection for capacity violations.
+    pub fn check_collection<R: Reportable>(reportable: &R) -> Result<(), Violations> {
+        Violations(Report::get(reportable.id()).violations()).into_result()
+    }
+
+    pub(crate) fn check_line_items(line_items: &[LineItem]) -> Result<(), Violations> {
+        Violations(line_items.iter().flat_map(LineItem::violations).collect()).into_result()
+    }
+}
+
+/// Panics if the given collection has grown past its max capacity.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::assert_within_capacity;
+/// use max_capacity::collections::HashMap;
+///
+/// let mut map = HashMap::with_capacity(3);
+/// map.insert(1, 2);
+///
+/// assert_within_capacity!(map);
+/// ```
+#[macro_export]
+macro_rules! assert_within_capacity {
+    ($collection:expr $(,)?) => {
+        if let Err(violations) = $crate::Report::check_collection(&$collection) {
+            panic!(
+                "assertion failed: `{}` is within capacity\n{}",
+                stringify!($collection),
+                violations
+            );
+        }
+    };
+}
+
+/// Panics if any collection in the report has grown past its max capacity.
+/// When given a [`ReportScope`](crate::ReportScope), only the collections
+/// created within that scope are checked.
+#[macro_export]
+macro_rules! assert_no_capacity_violations {
+    () => {
+        if let Err(violations) = $crate::Report::check() {
+            panic!("assertion failed: no capacity violations\n{}", violations);
+        }
+    };
+    ($scope:expr $(,)?) => {
+        if let Err(violations) = $scope.check() {
+            panic!("assertion failed: no capacity violations\n{}", violations);
+        }
+    };
+}
+
+#[test]
+fn test_violations() {
+    use crate::collections::HashMap;
+
+    let mut map = HashMap::<u32, ()>::with_capacity(3).with_name("my_map");
+
+    assert!(Report::check_collection(&map).is_ok());
+
+    map.insert(0, ());
+    map.insert(1, ());
+    map.insert(2, ());
+    map.insert(3, ());
+
+    let violations = Report::check_collection(&map).unwrap_err();
+
+    assert_eq!(
+        violations.0,
+        [Violation {
+            id: map.id(),
+            instance_name: "my_map".into(),
+            limit: Limit::Capacity,
+            max_capacity: 3,
+            capacity: 7,
+        }]
+    );
+    assert_eq!(
+        violations.to_string(),
+        format!(
+            "1 capacity violation(s):\n- {0} - my_map: max capacity 3\n+ {0} - my_map: capacity 7 (+4)",
+            map.id()
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "`vec` is within capacity")]
+fn test_assert_within_capacity() {
+    let mut vec = crate::vec::Vec::with_capacity(1);
+
+    vec.push(0);
+    vec.push(1);
+
+    assert_within_capacity!(vec);
+}
@@ -0,0 +1,461 @@
+use crate::{Error, Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::collections::binary_heap::{Drain, IntoIter, Iter, PeekMut};
+use std::collections::BinaryHeap as StdBinaryHeap;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+/// A `std::collections::BinaryHeap` which reports its capacity changes, along
+/// with its cached peak length.
+pub struct BinaryHeap<T>(StdBinaryHeap<T>, Tracker);
+
+// Std
+impl<T: Ord> BinaryHeap<T> {
+    /// Creates an empty `BinaryHeap` as a max-heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// heap.push(4);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty `BinaryHeap` with at least the specified capacity,
+    /// which also becomes its max capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::with_capacity(10);
+    /// heap.push(4);
+    /// assert!(heap.capacity() >= 10);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let heap = StdBinaryHeap::with_capacity(capacity);
+        let tracker = Tracker::new("BinaryHeap", heap.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(capacity), 0);
+
+        Self(heap, tracker)
+    }
+
+    /// Pushes an item onto the binary heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    #[track_caller]
+    pub fn push(&mut self, item: T) {
+        self.0.push(item);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Removes the greatest item from the binary heap and returns it, or
+    /// `None` if it is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let ret = self.0.pop();
+        self.observe_len();
+        ret
+    }
+
+    /// Returns a mutable reference to the greatest item in the binary heap,
+    /// or `None` if it is empty. The heap is restored once the `PeekMut` is
+    /// dropped.
+    #[inline]
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        self.0.peek_mut()
+    }
+
+    /// Consumes the `BinaryHeap` and returns a vector in sorted (ascending)
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from([4, 2, 1, 3]);
+    /// heap.push(6);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3, 3, 4, 6]);
+    /// ```
+    #[inline]
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.0.into_sorted_vec()
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+        self.observe_len();
+        other.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+}
+
+// Std
+impl<T> BinaryHeap<T> {
+    /// Returns an iterator visiting all values in the underlying vector, in
+    /// arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Returns the greatest item in the binary heap, or `None` if it is
+    /// empty.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.0.peek()
+    }
+
+    /// Returns the number of elements the binary heap can hold without
+    /// reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` elements more than the
+    /// current length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// heap.reserve(100);
+    /// assert!(heap.capacity() >= 100);
+    /// heap.push(4);
+    /// ```
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    /// Reserves the minimum capacity for at least `additional` elements more
+    /// than the current length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+        self.observe_capacity();
+    }
+
+    /// Tries to reserve capacity for at least `additional` elements more than
+    /// the current length.
+    ///
+    /// # Errors
+    ///
+    /// If the heap would need to hold more elements than its max capacity,
+    /// [`Error::HardCapRejected`] is returned and nothing is reserved.
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        self.1.check_hard_cap(self.0.len(), additional)?;
+        self.0.try_reserve(additional)?;
+        self.observe_capacity();
+
+        Ok(())
+    }
+
+    /// Discards as much additional capacity as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut heap: BinaryHeap<i32> = BinaryHeap::with_capacity(100);
+    ///
+    /// assert!(heap.capacity() >= 100);
+    /// heap.shrink_to_fit();
+    /// assert!(heap.capacity() == 0);
+    /// ```
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    /// Discards capacity with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length and the
+    /// supplied value. If the current capacity is less than the lower limit,
+    /// this is a no-op.
+    #[track_caller]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity);
+        self.observe_capacity();
+    }
+
+    /// Returns a slice of all values in the underlying vector, in arbitrary
+    /// order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+
+    /// Consumes the `BinaryHeap` and returns the underlying vector in
+    /// arbitrary order.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        self.0.into_vec()
+    }
+
+    /// Returns the length of the binary heap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if the binary heap is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the binary heap, returning an iterator over the removed
+    /// elements in arbitrary order.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.1.observe_len(0);
+        self.0.drain()
+    }
+
+    /// Drops all items from the binary heap. The capacity is left untouched.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+}
+
+impl<T> IntoIterator for BinaryHeap<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BinaryHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.0.iter()
+    }
+}
+
+impl<T: Clone> Clone for BinaryHeap<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+
+    #[inline]
+    fn clone_from(&mut self, other: &Self) {
+        self.0.clone_from(&other.0);
+        self.1 = other.1.cloned(self.0.capacity());
+    }
+}
+
+// Extra
+impl<T> BinaryHeap<T> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+impl<T> Display for BinaryHeap<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(fmt, "{name}: BinaryHeap<{}>", type_name::<T>())
+    }
+}
+
+// Std
+impl<T: Debug> Debug for BinaryHeap<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self(StdBinaryHeap::new(), Tracker::new("BinaryHeap", 0))
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for BinaryHeap<T> {
+    /// Converts a `Vec<T>` into a `BinaryHeap<T>`, in-place.
+    fn from(vec: Vec<T>) -> Self {
+        let heap = StdBinaryHeap::from(vec);
+        let tracker = Tracker::new("BinaryHeap", heap.capacity());
+        let mut heap = Self(heap, tracker);
+        heap.observe_len();
+        heap
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for BinaryHeap<T> {
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BinaryHeap;
+    ///
+    /// let mut h1 = BinaryHeap::from([1, 4, 2, 3]);
+    /// let mut h2: BinaryHeap<_> = [1, 4, 2, 3].into();
+    /// while let Some((a, b)) = h1.pop().zip(h2.pop()) {
+    ///     assert_eq!(a, b);
+    /// }
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        Self::from(Vec::from(arr))
+    }
+}
+
+impl<T> From<BinaryHeap<T>> for Vec<T> {
+    fn from(heap: BinaryHeap<T>) -> Vec<T> {
+        heap.into_vec()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BinaryHeap<T> {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryHeap<T> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<'a, T: 'a + Ord + Copy> Extend<&'a T> for BinaryHeap<T> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<T> Reportable for BinaryHeap<T> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_priority_queue_report() {
+    let mut heap = BinaryHeap::<u32>::with_capacity(2).with_name("my_heap");
+
+    heap.push(1);
+    heap.push(3);
+    heap.push(2);
+
+    let grown_cap = heap.capacity();
+
+    assert_eq!(heap.pop(), Some(3));
+
+    heap.extend([5, 4]);
+
+    let line_item = Report::get_line_item(&heap);
+
+    assert_eq!(line_item.type_name, "BinaryHeap");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(2));
+    assert_eq!(
+        line_item.events[1],
+        ReportEvent::CapacityIncrease(2, grown_cap)
+    );
+    assert_eq!(line_item.events.len(), 2);
+    assert_eq!(line_item.peak_len, 4);
+    assert!(matches!(
+        heap.try_reserve(1),
+        Err(Error::HardCapRejected {
+            max_capacity: 2,
+            requested: 5,
+            ..
+        })
+    ));
+    assert_eq!(heap.into_sorted_vec(), [1, 2, 4, 5]);
+}
@@ -0,0 +1,644 @@
+use crate::{LineItem, Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::borrow::Borrow;
+use std::collections::btree_map::{
+    Entry as StdEntry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, Range, RangeMut,
+    Values, ValuesMut,
+};
+use std::collections::BTreeMap as StdBTreeMap;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::mem;
+use std::ops::{Index, RangeBounds};
+
+/// A `std::collections::BTreeMap` which reports its length against a max
+/// length budget, along with its cached peak length.
+///
+/// B-trees allocate node by node and so have no capacity; their length stands
+/// in for it instead. The max length is set by `with_max_len` or a budget
+/// file, and the first peak past it is recorded as a `LenExceeded`, so that
+/// it's reported as a violation like any other collection. A budget for the
+/// memory held by the nodes can be set by `with_max_bytes` as well, which is
+/// recorded as a `BytesExceeded` once the estimate grows past it.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::collections::BTreeMap;
+///
+/// let mut map = BTreeMap::with_max_len(2).with_name("my_map");
+///
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// map.insert(3, "c");
+///
+/// assert!(max_capacity::Report::check_collection(&map).is_err());
+/// ```
+pub struct BTreeMap<K, V>(StdBTreeMap<K, V>, Tracker);
+
+/// The most elements a node of a std B-tree holds.
+const NODE_CAPACITY: usize = 11;
+
+/// Estimates the memory held by the nodes of a B-tree with `len` elements of
+/// `element_size` bytes, as if every node were a full leaf. Nodes are at
+/// least half full, so the real footprint is at most about twice this.
+pub(super) fn estimate_bytes(len: usize, element_size: usize) -> usize {
+    // Along with its elements, a node holds a pointer to its parent, its
+    // index within the parent and its length
+    let node_size =
+        NODE_CAPACITY * element_size + mem::size_of::<usize>() + 2 * mem::size_of::<u16>();
+
+    len.div_ceil(NODE_CAPACITY) * node_size
+}
+
+/// Publishes `len` for a B-tree, and if it's a new peak reports the first
+/// time it grows past its max length or max bytes since they were set.
+#[track_caller]
+pub(super) fn observe_peak(tracker: &mut Tracker, len: usize, element_size: usize) {
+    let id = tracker.id();
+
+    if !tracker.observe_len(len) || !Report::config().is_tracked(id) {
+        return;
+    }
+
+    let exceeded = unreported_excess(&Report::get(id), len, estimate_bytes(len, element_size));
+
+    for event in exceeded {
+        Report::record(id, event, len);
+    }
+}
+
+/// The budgets in effect which `len` and `bytes` exceed, unless that was
+/// already reported.
+fn unreported_excess(line_item: &LineItem, len: usize, bytes: usize) -> Vec<ReportEvent> {
+    let mut max_len = None;
+    let mut max_bytes = None;
+
+    for record in &line_item.events {
+        match record.event {
+            ReportEvent::MaxCapacitySet(max) => max_len = Some(max),
+            ReportEvent::MaxBytesSet(max) => max_bytes = Some(max),
+            ReportEvent::LenExceeded(..) => max_len = None,
+            ReportEvent::BytesExceeded(..) => max_bytes = None,
+            _ => (),
+        }
+    }
+
+    let len_exceeded = max_len
+        .filter(|&max_len| len > max_len)
+        .map(|max_len| ReportEvent::LenExceeded(max_len, len));
+    let bytes_exceeded = max_bytes
+        .filter(|&max_bytes| bytes > max_bytes)
+        .map(|max_bytes| ReportEvent::BytesExceeded(max_bytes, bytes));
+
+    len_exceeded.into_iter().chain(bytes_exceeded).collect()
+}
+
+// Std
+impl<K, V> BTreeMap<K, V> {
+    /// Makes a new, empty `BTreeMap`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a new, empty `BTreeMap` which should never hold more than
+    /// `max_len` elements.
+    #[inline]
+    #[track_caller]
+    pub fn with_max_len(max_len: usize) -> Self {
+        let tracker = Tracker::new("BTreeMap", 0);
+        tracker.record(ReportEvent::MaxCapacitySet(max_len), 0);
+
+        Self(StdBTreeMap::new(), tracker)
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the map, removing all elements.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Gets an iterator over the entries of the map, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the map, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the map, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.0.keys()
+    }
+
+    /// Gets an iterator over the values of the map, in order by key.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.0.values()
+    }
+
+    /// Gets a mutable iterator over the values of the map, in order by key.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Creates a consuming iterator visiting all the keys, in sorted order.
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        self.0.into_keys()
+    }
+
+    /// Creates a consuming iterator visiting all the values, in order by key.
+    #[inline]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        self.0.into_values()
+    }
+}
+
+// Std
+impl<K: Ord, V> BTreeMap<K, V> {
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get(key)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get_key_value(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get_mut(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.contains_key(key)
+    }
+
+    /// Returns the first key-value pair in the map, the key being the
+    /// minimum key.
+    #[inline]
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.0.first_key_value()
+    }
+
+    /// Returns the last key-value pair in the map, the key being the maximum
+    /// key.
+    #[inline]
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.0.last_key_value()
+    }
+
+    /// Removes and returns the first element in the map.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let ret = self.0.pop_first();
+        self.observe_len();
+        ret
+    }
+
+    /// Removes and returns the last element in the map.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let ret = self.0.pop_last();
+        self.observe_len();
+        ret
+    }
+
+    /// Inserts a key-value pair into the map, returning the old value if the
+    /// key was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BTreeMap;
+    ///
+    /// let mut map = BTreeMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.is_empty(), false);
+    ///
+    /// map.insert(37, "b");
+    /// assert_eq!(map.insert(37, "c"), Some("b"));
+    /// assert_eq!(map[&37], "c");
+    /// ```
+    #[track_caller]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let ret = self.0.insert(key, value);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ret = self.0.remove(key);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ret = self.0.remove_entry(key);
+        self.observe_len();
+        ret
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+
+    /// Moves all elements from `other` into `self`, leaving `other` empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+        self.observe_len();
+        other.observe_len();
+    }
+
+    /// Constructs a double-ended iterator over a sub-range of elements in the
+    /// map.
+    #[inline]
+    pub fn range<T, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        T: Ord + ?Sized,
+        K: Borrow<T>,
+        R: RangeBounds<T>,
+    {
+        self.0.range(range)
+    }
+
+    /// Constructs a mutable double-ended iterator over a sub-range of
+    /// elements in the map.
+    #[inline]
+    pub fn range_mut<T, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        T: Ord + ?Sized,
+        K: Borrow<T>,
+        R: RangeBounds<T>,
+    {
+        self.0.range_mut(range)
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place
+    /// manipulation.
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry {
+            len: self.0.len(),
+            entry: self.0.entry(key),
+            tracker: &mut self.1,
+        }
+    }
+}
+
+impl<K, V> IntoIterator for BTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.0.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut BTreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.0.iter_mut()
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for BTreeMap<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(0);
+
+        Self(inner, tracker)
+    }
+}
+
+// Extra
+impl<K, V> BTreeMap<K, V> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    /// Sets the most memory the map's nodes should hold, as estimated by
+    /// `estimated_bytes`.
+    #[track_caller]
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.1
+            .record(ReportEvent::MaxBytesSet(max_bytes), self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.set_max_bytes(max_bytes);
+        self
+    }
+
+    /// Estimates the memory held by the map's nodes, as if every node were
+    /// full.
+    pub fn estimated_bytes(&self) -> usize {
+        estimate_bytes(self.0.len(), Self::ELEMENT_SIZE)
+    }
+
+    const ELEMENT_SIZE: usize = mem::size_of::<K>() + mem::size_of::<V>();
+
+    #[inline]
+    #[track_caller]
+    fn observe_len(&mut self) {
+        observe_peak(&mut self.1, self.0.len(), Self::ELEMENT_SIZE);
+    }
+}
+
+/// A view into a single entry of a [`BTreeMap`], which may be vacant or
+/// occupied. Inserting through it is reported like any other insert.
+pub struct Entry<'a, K, V> {
+    entry: StdEntry<'a, K, V>,
+    tracker: &'a mut Tracker,
+    /// The length of the map before inserting into the entry.
+    len: usize,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting `default` if empty, and
+    /// returns a mutable reference to the value in the entry.
+    #[inline]
+    #[track_caller]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with_key(|_| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default`
+    /// if empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    #[track_caller]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Same as `or_insert_with`, but `default` is given the entry's key.
+    #[track_caller]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self.entry {
+            StdEntry::Occupied(entry) => entry.into_mut(),
+            StdEntry::Vacant(entry) => {
+                let value = default(entry.key());
+                let value = entry.insert(value);
+
+                observe_peak(self.tracker, self.len + 1, BTreeMap::<K, V>::ELEMENT_SIZE);
+                value
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        Entry {
+            entry: self.entry.and_modify(f),
+            ..self
+        }
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if
+    /// empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    #[track_caller]
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with_key(|_| V::default())
+    }
+}
+
+impl<K, V> Display for BTreeMap<K, V> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(
+            fmt,
+            "{name}: BTreeMap<{}, {}>",
+            type_name::<K>(),
+            type_name::<V>(),
+        )
+    }
+}
+
+// Std
+impl<K: Debug, V: Debug> Debug for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    fn default() -> Self {
+        Self(StdBTreeMap::new(), Tracker::new("BTreeMap", 0))
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for BTreeMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for BTreeMap<K, V> {}
+
+impl<K, Q: ?Sized, V> Index<&Q> for BTreeMap<K, V>
+where
+    K: Borrow<Q> + Ord,
+    Q: Ord,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `BTreeMap`.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.0.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Ord, V, const N: usize> From<[(K, V); N]> for BTreeMap<K, V> {
+    #[track_caller]
+    fn from(arr: [(K, V); N]) -> Self {
+        let mut map = Self::new();
+        map.extend(arr);
+        map
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    #[track_caller]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> BTreeMap<K, V> {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BTreeMap<K, V> {
+    #[inline]
+    #[track_caller]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.observe_len();
+    }
+}
+
+impl<'a, K: Ord + Copy, V: Copy> Extend<(&'a K, &'a V)> for BTreeMap<K, V> {
+    #[inline]
+    #[track_caller]
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.observe_len();
+    }
+}
+
+impl<K, V> Reportable for BTreeMap<K, V> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_max_len_report() {
+    use crate::Limit;
+
+    let mut map = BTreeMap::with_max_len(2).with_name("my_btree_map");
+
+    map.insert(0, ());
+    *map.entry(1).or_default() = ();
+    map.remove(&0);
+    map.insert(2, ());
+    map.entry(3).or_insert(());
+    map.insert(4, ());
+
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(line_item.type_name, "BTreeMap");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(2));
+    assert_eq!(line_item.events[1], ReportEvent::LenExceeded(2, 3));
+    assert_eq!(line_item.events.len(), 2);
+    assert_eq!(line_item.peak_len, 4);
+    assert_eq!(line_item.capacity, 0);
+    assert_eq!(line_item.reallocations(), 0);
+    assert_eq!(line_item.violations()[0].limit, Limit::Len);
+    assert_eq!(line_item.violations()[0].capacity, 3);
+}
+
+#[test]
+fn test_max_bytes_report() {
+    let mut map = BTreeMap::<u64, u64>::new()
+        .with_max_bytes(estimate_bytes(11, 16))
+        .with_name("my_btree_bytes");
+
+    map.extend((0..11).map(|i| (i, i)));
+    map.insert(11, 11);
+    map.insert(12, 12);
+
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(estimate_bytes(11, 16), 188);
+    assert_eq!(map.estimated_bytes(), 376);
+    assert_eq!(line_item.events[1], ReportEvent::BytesExceeded(188, 376));
+    assert_eq!(line_item.events.len(), 2);
+    assert_eq!(line_item.peak_len, 13);
+}
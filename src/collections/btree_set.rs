@@ -0,0 +1,424 @@
+use crate::collections::btree_map::{estimate_bytes, observe_peak};
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::borrow::Borrow;
+use std::collections::btree_set::{
+    Difference, Intersection, IntoIter, Iter, Range, SymmetricDifference, Union,
+};
+use std::collections::BTreeSet as StdBTreeSet;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::mem;
+use std::ops::RangeBounds;
+
+/// A `std::collections::BTreeSet` which reports its length against a max
+/// length budget, along with its cached peak length.
+///
+/// As with [`BTreeMap`](crate::collections::BTreeMap), the first peak past
+/// the max length is recorded as a `LenExceeded`, and the first past the max
+/// bytes as a `BytesExceeded`.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::collections::BTreeSet;
+///
+/// let mut set = BTreeSet::with_max_len(2).with_name("my_set");
+///
+/// set.insert("a");
+/// set.insert("b");
+/// set.insert("b");
+///
+/// assert!(max_capacity::Report::check_collection(&set).is_ok());
+/// ```
+pub struct BTreeSet<T>(StdBTreeSet<T>, Tracker);
+
+// Std
+impl<T> BTreeSet<T> {
+    /// Makes a new, empty `BTreeSet`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a new, empty `BTreeSet` which should never hold more than
+    /// `max_len` elements.
+    #[inline]
+    #[track_caller]
+    pub fn with_max_len(max_len: usize) -> Self {
+        let tracker = Tracker::new("BTreeSet", 0);
+        tracker.record(ReportEvent::MaxCapacitySet(max_len), 0);
+
+        Self(StdBTreeSet::new(), tracker)
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the set, removing all elements.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Gets an iterator that visits the elements in the set in ascending
+    /// order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+}
+
+// Std
+impl<T: Ord> BTreeSet<T> {
+    /// Returns `true` if the set contains an element equal to the value.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.contains(value)
+    }
+
+    /// Returns a reference to the element in the set, if any, that is equal
+    /// to the value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get(value)
+    }
+
+    /// Returns a reference to the first element in the set, if any.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.0.first()
+    }
+
+    /// Returns a reference to the last element in the set, if any.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    /// Removes the first element from the set and returns it, if any.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<T> {
+        let ret = self.0.pop_first();
+        self.observe_len();
+        ret
+    }
+
+    /// Removes the last element from the set and returns it, if any.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<T> {
+        let ret = self.0.pop_last();
+        self.observe_len();
+        ret
+    }
+
+    /// Adds a value to the set, returning whether it was newly inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::BTreeSet;
+    ///
+    /// let mut set = BTreeSet::new();
+    ///
+    /// assert_eq!(set.insert(2), true);
+    /// assert_eq!(set.insert(2), false);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    #[track_caller]
+    pub fn insert(&mut self, value: T) -> bool {
+        let ret = self.0.insert(value);
+        self.observe_len();
+        ret
+    }
+
+    /// Adds a value to the set, replacing and returning the existing element
+    /// equal to it, if any.
+    #[track_caller]
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        let ret = self.0.replace(value);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes a value from the set, returning whether it was present.
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ret = self.0.remove(value);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes and returns the element in the set, if any, that is equal to
+    /// the value.
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ret = self.0.take(value);
+        self.observe_len();
+        ret
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+
+    /// Moves all elements from `other` into `self`, leaving `other` empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+        self.observe_len();
+        other.observe_len();
+    }
+
+    /// Constructs a double-ended iterator over a sub-range of elements in the
+    /// set.
+    #[inline]
+    pub fn range<K, R>(&self, range: R) -> Range<'_, T>
+    where
+        K: Ord + ?Sized,
+        T: Borrow<K>,
+        R: RangeBounds<K>,
+    {
+        self.0.range(range)
+    }
+
+    /// Visits the elements representing the difference, i.e., the elements
+    /// that are in `self` but not in `other`, in ascending order.
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T> {
+        self.0.difference(&other.0)
+    }
+
+    /// Visits the elements representing the symmetric difference, i.e., the
+    /// elements that are in `self` or in `other` but not in both, in
+    /// ascending order.
+    #[inline]
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T> {
+        self.0.symmetric_difference(&other.0)
+    }
+
+    /// Visits the elements representing the intersection, i.e., the elements
+    /// that are both in `self` and `other`, in ascending order.
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T> {
+        self.0.intersection(&other.0)
+    }
+
+    /// Visits the elements representing the union, i.e., all the elements in
+    /// `self` or `other`, without duplicates, in ascending order.
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T> {
+        self.0.union(&other.0)
+    }
+
+    /// Returns `true` if `self` has no elements in common with `other`.
+    #[inline]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.is_disjoint(&other.0)
+    }
+
+    /// Returns `true` if the set is a subset of another.
+    #[inline]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.is_subset(&other.0)
+    }
+
+    /// Returns `true` if the set is a superset of another.
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.0.is_superset(&other.0)
+    }
+}
+
+impl<T> IntoIterator for BTreeSet<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BTreeSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.0.iter()
+    }
+}
+
+impl<T: Clone> Clone for BTreeSet<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(0);
+
+        Self(inner, tracker)
+    }
+}
+
+// Extra
+impl<T> BTreeSet<T> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    /// Sets the most memory the set's nodes should hold, as estimated by
+    /// `estimated_bytes`.
+    #[track_caller]
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.1
+            .record(ReportEvent::MaxBytesSet(max_bytes), self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.set_max_bytes(max_bytes);
+        self
+    }
+
+    /// Estimates the memory held by the set's nodes, as if every node were
+    /// full.
+    pub fn estimated_bytes(&self) -> usize {
+        estimate_bytes(self.0.len(), mem::size_of::<T>())
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_len(&mut self) {
+        observe_peak(&mut self.1, self.0.len(), mem::size_of::<T>());
+    }
+}
+
+impl<T> Display for BTreeSet<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(fmt, "{name}: BTreeSet<{}>", type_name::<T>())
+    }
+}
+
+// Std
+impl<T: Debug> Debug for BTreeSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Default for BTreeSet<T> {
+    fn default() -> Self {
+        Self(StdBTreeSet::new(), Tracker::new("BTreeSet", 0))
+    }
+}
+
+impl<T: PartialEq> PartialEq for BTreeSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq> Eq for BTreeSet<T> {}
+
+impl<T: Ord, const N: usize> From<[T; N]> for BTreeSet<T> {
+    #[track_caller]
+    fn from(arr: [T; N]) -> Self {
+        let mut set = Self::new();
+        set.extend(arr);
+        set
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BTreeSet<T> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BTreeSet<T> {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Ord> Extend<T> for BTreeSet<T> {
+    #[inline]
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+    }
+}
+
+impl<'a, T: 'a + Ord + Copy> Extend<&'a T> for BTreeSet<T> {
+    #[inline]
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+    }
+}
+
+impl<T> Reportable for BTreeSet<T> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_max_len_report() {
+    let mut set = BTreeSet::new().with_name("my_btree_set");
+
+    set.extend([3, 1, 2]);
+    set.pop_first();
+    set.insert(4);
+
+    let line_item = Report::get_line_item(&set);
+
+    assert_eq!(line_item.type_name, "BTreeSet");
+    assert!(line_item.events.is_empty());
+    assert_eq!(line_item.peak_len, 3);
+    assert!(line_item.violations().is_empty());
+}
@@ -1,18 +1,19 @@
-use crate::sealed::Reportable;
-use crate::{Report, ReportEvent, Uid};
+use crate::{Error, Report, ReportEvent, Reportable, Tracker, Uid};
 
 use std::any::type_name;
 use std::borrow::Borrow;
 use std::collections::hash_map::{
-    Drain, Entry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, RandomState, Values,
-    ValuesMut,
+    Drain, Entry as StdEntry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, RandomState,
+    Values, ValuesMut,
 };
-use std::collections::{HashMap as StdHashMap, TryReserveError};
+use std::collections::HashMap as StdHashMap;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::hash::{BuildHasher, Hash};
 use std::ops::Index;
 
-pub struct HashMap<K, V, S = RandomState>(StdHashMap<K, V, S>, Uid);
+/// A `std::collections::HashMap` which reports its capacity changes, along
+/// with its cached peak length.
+pub struct HashMap<K, V, S = RandomState>(StdHashMap<K, V, S>, Tracker);
 
 // Std
 impl<K, V> HashMap<K, V, RandomState> {
@@ -44,6 +45,7 @@ impl<K, V> HashMap<K, V, RandomState> {
     /// let mut map: HashMap<&str, i32> = HashMap::with_capacity(10);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn with_capacity(capacity: usize) -> Self {
         Self::with_capacity_and_hasher(capacity, Default::default())
     }
@@ -78,7 +80,7 @@ impl<K, V, S> HashMap<K, V, S> {
     pub fn with_hasher(hash_builder: S) -> HashMap<K, V, S> {
         Self(
             StdHashMap::with_hasher(hash_builder),
-            Report::new_line_item(),
+            Tracker::new("HashMap", 0),
         )
     }
 
@@ -107,16 +109,13 @@ impl<K, V, S> HashMap<K, V, S> {
     /// map.insert(1, 2);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> HashMap<K, V, S> {
-        let id = Report::new_line_item();
-        let mut li = Report::get_mut(id);
+        let map = StdHashMap::with_capacity_and_hasher(capacity, hash_builder);
+        let tracker = Tracker::new("HashMap", map.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(capacity), 0);
 
-        li.events.push(ReportEvent::MaxCapacitySet(capacity));
-
-        Self(
-            StdHashMap::with_capacity_and_hasher(capacity, hash_builder),
-            id,
-        )
+        Self(map, tracker)
     }
 
     /// Returns the number of elements the map can hold without reallocating.
@@ -366,6 +365,7 @@ impl<K, V, S> HashMap<K, V, S> {
     /// ```
     #[inline]
     pub fn drain(&mut self) -> Drain<'_, K, V> {
+        self.1.observe_len(0);
         self.0.drain()
     }
 
@@ -388,7 +388,8 @@ impl<K, V, S> HashMap<K, V, S> {
     where
         F: FnMut(&K, &mut V) -> bool,
     {
-        self.0.retain(f)
+        self.0.retain(f);
+        self.observe_len();
     }
 
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory
@@ -407,6 +408,7 @@ impl<K, V, S> HashMap<K, V, S> {
     #[inline]
     pub fn clear(&mut self) {
         self.0.clear();
+        self.observe_len();
     }
 
     /// Returns a reference to the map's [`BuildHasher`].
@@ -449,17 +451,10 @@ where
     /// map.reserve(10);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn reserve(&mut self, additional: usize) {
-        let current_cap = self.0.capacity();
         self.0.reserve(additional);
-
-        if self.0.capacity() < current_cap {
-            let mut report = Report::get_mut(self.1);
-            report.events.push(ReportEvent::CapacityIncrease(
-                current_cap,
-                self.0.capacity(),
-            ));
-        }
+        self.observe_capacity();
     }
 
     /// Tries to reserve capacity for at least `additional` more elements to be inserted
@@ -468,6 +463,9 @@ where
     ///
     /// # Errors
     ///
+    /// If the map would need to hold more elements than its max capacity,
+    /// [`Error::HardCapRejected`] is returned and nothing is reserved.
+    ///
     /// If the capacity overflows, or the allocator reports a failure, then an error
     /// is returned.
     ///
@@ -480,8 +478,13 @@ where
     /// map.try_reserve(10).expect("why is the test harness OOMing on 10 bytes?");
     /// ```
     #[inline]
-    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        self.0.try_reserve(additional)
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        self.1.check_hard_cap(self.0.len(), additional)?;
+        self.0.try_reserve(additional)?;
+        self.observe_capacity();
+
+        Ok(())
     }
 
     /// Shrinks the capacity of the map as much as possible. It will drop
@@ -501,17 +504,10 @@ where
     /// assert!(map.capacity() >= 2);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn shrink_to_fit(&mut self) {
-        let current_cap = self.0.capacity();
         self.0.shrink_to_fit();
-
-        if self.0.capacity() < current_cap {
-            let mut report = Report::get_mut(self.1);
-            report.events.push(ReportEvent::CapacityDecrease(
-                current_cap,
-                self.0.capacity(),
-            ));
-        }
+        self.observe_capacity();
     }
 
     /// Shrinks the capacity of the map with a lower limit. It will drop
@@ -535,17 +531,10 @@ where
     /// assert!(map.capacity() >= 2);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn shrink_to(&mut self, min_capacity: usize) {
-        let current_cap = self.0.capacity();
         self.0.shrink_to(min_capacity);
-
-        if self.0.capacity() < current_cap {
-            let mut report = Report::get_mut(self.1);
-            report.events.push(ReportEvent::CapacityDecrease(
-                current_cap,
-                self.0.capacity(),
-            ));
-        }
+        self.observe_capacity();
     }
 
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
@@ -568,8 +557,19 @@ where
     /// assert_eq!(letters.get(&'y'), None);
     /// ```
     #[inline]
+    #[track_caller]
     pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
-        self.0.entry(key)
+        // A vacant entry can't grow the map it borrows without hiding the new
+        // capacity, so a full map makes room up front unless the key is in it
+        if self.0.len() == self.0.capacity() && !self.0.contains_key(&key) {
+            self.reserve(1);
+        }
+
+        Entry {
+            len: self.0.len(),
+            entry: self.0.entry(key),
+            tracker: &mut self.1,
+        }
     }
 
     /// Returns a reference to the value corresponding to the key.
@@ -589,10 +589,10 @@ where
     /// assert_eq!(map.get(&2), None);
     /// ```
     #[inline]
-    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
         self.0.get(k)
     }
@@ -614,10 +614,10 @@ where
     /// assert_eq!(map.get_key_value(&2), None);
     /// ```
     #[inline]
-    pub fn get_key_value<Q: ?Sized>(&self, k: &Q) -> Option<(&K, &V)>
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
         self.0.get_key_value(k)
     }
@@ -639,10 +639,10 @@ where
     /// assert_eq!(map.contains_key(&2), false);
     /// ```
     #[inline]
-    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
         self.0.contains_key(k)
     }
@@ -666,10 +666,10 @@ where
     /// assert_eq!(map[&1], "b");
     /// ```
     #[inline]
-    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
         self.0.get_mut(k)
     }
@@ -700,18 +700,11 @@ where
     /// assert_eq!(map.insert(37, "c"), Some("b"));
     /// assert_eq!(map[&37], "c");
     /// ```
+    #[track_caller]
     pub fn insert(&mut self, k: K, v: V) -> Option<V> {
-        let current_cap = self.0.capacity();
         let ret = self.0.insert(k, v);
-        let exceeds_cap = self.0.capacity() > current_cap;
-
-        if exceeds_cap {
-            let mut report = Report::get_mut(self.1);
-            report.events.push(ReportEvent::CapacityIncrease(
-                current_cap,
-                self.0.capacity(),
-            ));
-        }
+        self.observe_len();
+        self.observe_capacity();
 
         ret
     }
@@ -734,12 +727,14 @@ where
     /// assert_eq!(map.remove(&1), None);
     /// ```
     #[inline]
-    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
-        self.0.remove(k)
+        let ret = self.0.remove(k);
+        self.observe_len();
+        ret
     }
 
     /// Removes a key from the map, returning the stored key and value if the
@@ -762,12 +757,14 @@ where
     /// # }
     /// ```
     #[inline]
-    pub fn remove_entry<Q: ?Sized>(&mut self, k: &Q) -> Option<(K, V)>
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: ?Sized + Hash + Eq,
     {
-        self.0.remove_entry(k)
+        let ret = self.0.remove_entry(k);
+        self.observe_len();
+        ret
     }
 }
 
@@ -829,32 +826,118 @@ where
 {
     #[inline]
     fn clone(&self) -> Self {
-        Self(self.0.clone(), self.1)
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
     }
 
     #[inline]
     fn clone_from(&mut self, other: &Self) {
         self.0.clone_from(&other.0);
-        self.1.clone_from(&other.1);
+        self.1 = other.1.cloned(self.0.capacity());
     }
 }
 
 // Extra
 impl<K, V, S> HashMap<K, V, S> {
+    #[track_caller]
     pub fn set_name(&mut self, name: &str) {
-        let mut report = Report::get_mut(self.1);
-        report.instance_name = name.into();
+        self.1.set_name(name, self.0.len());
     }
 
+    #[track_caller]
     pub fn with_name(mut self, name: &str) -> Self {
         self.set_name(name);
         self
     }
+
+    /// Marks a point in the collection's history, such as the start of a
+    /// phase, which is shown inline in its event table.
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+/// A view into a single entry of a [`HashMap`], which may be vacant or
+/// occupied. Inserting through it is reported like any other insert.
+pub struct Entry<'a, K, V> {
+    entry: StdEntry<'a, K, V>,
+    tracker: &'a mut Tracker,
+    /// The length of the map before inserting into the entry.
+    len: usize,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting `default` if empty, and
+    /// returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with_key(|_| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default`
+    /// if empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Same as `or_insert_with`, but `default` is given the entry's key.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self.entry {
+            StdEntry::Occupied(entry) => entry.into_mut(),
+            StdEntry::Vacant(entry) => {
+                let value = default(entry.key());
+
+                self.tracker.observe_len(self.len + 1);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        Entry {
+            entry: self.entry.and_modify(f),
+            ..self
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if
+    /// empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with_key(|_| V::default())
+    }
 }
 
 impl<K, V, S> Display for HashMap<K, V, S> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
-        let name = &Report::get(self.1).instance_name;
+        let name = &Report::get(self.1.id()).instance_name;
         write!(
             fmt,
             "{name}: HashMap<{}, {}>",
@@ -877,7 +960,7 @@ where
 
 impl<K, V, S: Default> Default for HashMap<K, V, S> {
     fn default() -> Self {
-        Self(StdHashMap::default(), Report::new_line_item())
+        Self(StdHashMap::default(), Tracker::new("HashMap", 0))
     }
 }
 
@@ -913,8 +996,11 @@ where
     /// let map2: HashMap<_, _> = [(1, 2), (3, 4)].into();
     /// assert_eq!(map1, map2);
     /// ```
+    #[track_caller]
     fn from(arr: [(K, V); N]) -> Self {
-        Self(StdHashMap::from_iter(arr), Report::new_line_item())
+        let mut map = HashMap::new();
+        map.extend(arr);
+        map
     }
 }
 
@@ -936,8 +1022,11 @@ where
     S: BuildHasher,
 {
     #[inline]
+    #[track_caller]
     fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
-        self.0.extend(iter)
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
     }
 
     // #[inline]
@@ -958,8 +1047,11 @@ where
     S: BuildHasher,
 {
     #[inline]
+    #[track_caller]
     fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
-        self.0.extend(iter)
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
     }
 
     // #[inline]
@@ -975,11 +1067,10 @@ where
 
 impl<K, V, S> Reportable for HashMap<K, V, S> {
     fn id(&self) -> Uid {
-        self.1
+        self.1.id()
     }
 }
 
-// TODO: Allocations via the entry API need to be supported
 #[test]
 fn test_basic_report() {
     let mut map = HashMap::<u32, ()>::new().with_name("my_map");
@@ -1029,3 +1120,49 @@ fn test_exceeds_capacity_report() {
     assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(3, 7));
     assert_eq!(line_item.events.len(), 2);
 }
+
+#[test]
+fn test_try_reserve_hard_cap() {
+    let mut map = HashMap::<u32, ()>::with_capacity(4).with_name("my_map");
+
+    map.insert(0, ());
+
+    assert!(map.try_reserve(3).is_ok());
+    assert!(matches!(
+        map.try_reserve(4),
+        Err(Error::HardCapRejected {
+            max_capacity: 4,
+            requested: 5,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_entry_and_extend_report() {
+    let mut map = HashMap::<u32, ()>::with_capacity(3).with_name("my_map");
+
+    for i in 0..4 {
+        map.entry(i).or_insert(());
+    }
+
+    let mut extended = HashMap::<u32, ()>::with_capacity(3).with_name("my_map");
+
+    extended.extend((0..100).map(|i| (i, ())));
+
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(3, 7));
+    assert_eq!(line_item.peak_len, 4);
+    assert!(Report::check_collection(&map).is_err());
+
+    let line_item = Report::get_line_item(&extended);
+
+    assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(3, 112));
+    assert_eq!(line_item.peak_len, 100);
+    assert!(Report::check_collection(&extended).is_err());
+    assert_eq!(
+        Report::get_line_item(&HashMap::from([(0, ()), (1, ())])).events[0],
+        ReportEvent::CapacityIncrease(0, 3)
+    );
+}
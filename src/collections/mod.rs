@@ -1,5 +1,13 @@
+pub mod binary_heap;
+pub mod btree_map;
+pub mod btree_set;
 pub mod hash_map;
 pub mod hash_set;
+pub mod vec_deque;
 
+pub use binary_heap::BinaryHeap;
+pub use btree_map::BTreeMap;
+pub use btree_set::BTreeSet;
 pub use hash_map::HashMap;
 pub use hash_set::HashSet;
+pub use vec_deque::VecDeque;
@@ -0,0 +1,622 @@
+use crate::{Error, Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::collections::vec_deque::{Drain, IntoIter, Iter, IterMut};
+use std::collections::VecDeque as StdVecDeque;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::ops::{Index, IndexMut, RangeBounds};
+
+/// A `std::collections::VecDeque` which reports its capacity changes, along
+/// with its cached peak length.
+pub struct VecDeque<T>(StdVecDeque<T>, Tracker);
+
+// Std
+impl<T> VecDeque<T> {
+    /// Creates an empty deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let deque: VecDeque<u32> = VecDeque::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty deque with space for at least `capacity` elements,
+    /// which also becomes its max capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let deque: VecDeque<u32> = VecDeque::with_capacity(10);
+    /// assert!(deque.capacity() >= 10);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let deque = StdVecDeque::with_capacity(capacity);
+        let tracker = Tracker::new("VecDeque", deque.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(capacity), 0);
+
+        Self(deque, tracker)
+    }
+
+    /// Returns the number of elements the deque can hold without
+    /// reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns the number of elements in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the deque is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Provides a reference to the element at the given index.
+    ///
+    /// Element at index 0 is the front of the queue.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.0.get(index)
+    }
+
+    /// Provides a mutable reference to the element at the given index.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Provides a reference to the front element, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.0.front()
+    }
+
+    /// Provides a mutable reference to the front element, or `None` if the
+    /// deque is empty.
+    #[inline]
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.0.front_mut()
+    }
+
+    /// Provides a reference to the back element, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.0.back()
+    }
+
+    /// Provides a mutable reference to the back element, or `None` if the
+    /// deque is empty.
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.0.back_mut()
+    }
+
+    /// Appends an element to the back of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut buf = VecDeque::new();
+    /// buf.push_back(1);
+    /// buf.push_back(3);
+    /// assert_eq!(3, *buf.back().unwrap());
+    /// ```
+    #[track_caller]
+    pub fn push_back(&mut self, value: T) {
+        self.0.push_back(value);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Prepends an element to the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut d = VecDeque::new();
+    /// d.push_front(1);
+    /// d.push_front(2);
+    /// assert_eq!(d.front(), Some(&2));
+    /// ```
+    #[track_caller]
+    pub fn push_front(&mut self, value: T) {
+        self.0.push_front(value);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Removes the last element and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        let ret = self.0.pop_back();
+        self.observe_len();
+        ret
+    }
+
+    /// Removes the first element and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        let ret = self.0.pop_front();
+        self.observe_len();
+        ret
+    }
+
+    /// Inserts an element at `index` within the deque, shifting all elements
+    /// with indices greater than or equal to `index` towards the back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the deque's length.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.0.insert(index, value);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Removes and returns the element at `index` from the deque, or `None`
+    /// if `index` is out of bounds.
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let ret = self.0.remove(index);
+        self.observe_len();
+        ret
+    }
+
+    /// Swaps elements at indices `i` and `j`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    #[inline]
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.0.swap(i, j)
+    }
+
+    /// Shortens the deque, keeping the first `len` elements and dropping the
+    /// rest. The capacity is left untouched.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+        self.observe_len();
+    }
+
+    /// Clears the deque, removing all values. The capacity is left untouched.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Returns `true` if the deque contains an element equal to the given
+    /// value.
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.0.contains(x)
+    }
+
+    /// Returns a front-to-back iterator.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Returns a front-to-back iterator that returns mutable references.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.0.iter_mut()
+    }
+
+    /// Removes the specified range from the deque in bulk, returning all
+    /// removed elements as an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the
+    /// end point is greater than the length of the deque.
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.1.observe_drain(self.0.len(), &range);
+        self.0.drain(range)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+        self.observe_len();
+        other.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be
+    /// inserted in the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut buf: VecDeque<i32> = [1].into();
+    /// buf.reserve(10);
+    /// assert!(buf.capacity() >= 11);
+    /// ```
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    /// Reserves the minimum capacity for at least `additional` more elements
+    /// to be inserted in the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+        self.observe_capacity();
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements to
+    /// be inserted in the deque.
+    ///
+    /// # Errors
+    ///
+    /// If the deque would need to hold more elements than its max capacity,
+    /// [`Error::HardCapRejected`] is returned and nothing is reserved.
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut buf: VecDeque<u32> = VecDeque::with_capacity(4);
+    /// assert!(buf.try_reserve(4).is_ok());
+    /// assert!(buf.try_reserve(5).is_err());
+    /// ```
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        self.1.check_hard_cap(self.0.len(), additional)?;
+        self.0.try_reserve(additional)?;
+        self.observe_capacity();
+
+        Ok(())
+    }
+
+    /// Shrinks the capacity of the deque as much as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut buf = VecDeque::with_capacity(15);
+    /// buf.extend(0..4);
+    /// assert!(buf.capacity() >= 15);
+    /// buf.shrink_to_fit();
+    /// assert!(buf.capacity() >= 4);
+    /// ```
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the deque with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length and the
+    /// supplied value. If the current capacity is less than the lower limit,
+    /// this is a no-op.
+    #[track_caller]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity);
+        self.observe_capacity();
+    }
+
+    /// Rearranges the internal storage of the deque so it is one contiguous
+    /// slice, which is then returned. This never reallocates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let mut buf = VecDeque::with_capacity(15);
+    /// buf.push_back(2);
+    /// buf.push_back(1);
+    /// buf.push_front(3);
+    /// assert_eq!(buf.make_contiguous(), &[3, 2, 1]);
+    /// ```
+    #[inline]
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.0.make_contiguous()
+    }
+
+    /// Returns a pair of slices which contain, in order, the contents of the
+    /// deque.
+    #[inline]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.0.as_slices()
+    }
+
+    /// Returns a pair of mutable slices which contain, in order, the contents
+    /// of the deque.
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        self.0.as_mut_slices()
+    }
+
+    /// Rotates the deque `n` places to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `len()`.
+    #[inline]
+    pub fn rotate_left(&mut self, n: usize) {
+        self.0.rotate_left(n)
+    }
+
+    /// Rotates the deque `n` places to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `len()`.
+    #[inline]
+    pub fn rotate_right(&mut self, n: usize) {
+        self.0.rotate_right(n)
+    }
+}
+
+impl<T> IntoIterator for VecDeque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a VecDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.0.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut VecDeque<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.0.iter_mut()
+    }
+}
+
+impl<T: Clone> Clone for VecDeque<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+
+    #[inline]
+    fn clone_from(&mut self, other: &Self) {
+        self.0.clone_from(&other.0);
+        self.1 = other.1.cloned(self.0.capacity());
+    }
+}
+
+// Extra
+impl<T> VecDeque<T> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+impl<T> Display for VecDeque<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(fmt, "{name}: VecDeque<{}>", type_name::<T>())
+    }
+}
+
+// Std
+impl<T: Debug> Debug for VecDeque<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Default for VecDeque<T> {
+    fn default() -> Self {
+        Self(StdVecDeque::new(), Tracker::new("VecDeque", 0))
+    }
+}
+
+impl<T: PartialEq> PartialEq for VecDeque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq> Eq for VecDeque<T> {}
+
+impl<T> Index<usize> for VecDeque<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        &self.0[index]
+    }
+}
+
+impl<T> IndexMut<usize> for VecDeque<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.0[index]
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for VecDeque<T> {
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::collections::VecDeque;
+    ///
+    /// let deq1 = VecDeque::from([1, 2, 3, 4]);
+    /// let deq2: VecDeque<_> = [1, 2, 3, 4].into();
+    /// assert_eq!(deq1, deq2);
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        let deque = StdVecDeque::from(arr);
+        let tracker = Tracker::new("VecDeque", deque.capacity());
+        let mut deque = Self(deque, tracker);
+        deque.observe_len();
+        deque
+    }
+}
+
+impl<T> FromIterator<T> for VecDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> VecDeque<T> {
+        let mut deque = VecDeque::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T> Extend<T> for VecDeque<T> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for VecDeque<T> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<T> Reportable for VecDeque<T> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_ring_buffer_report() {
+    let mut deque = VecDeque::<u32>::with_capacity(4).with_name("my_deque");
+    let initial_cap = deque.capacity();
+
+    for i in 0..initial_cap as u32 {
+        deque.push_back(i);
+        deque.pop_front();
+    }
+
+    deque.extend(0..initial_cap as u32);
+    deque.push_front(0);
+
+    let grown_cap = deque.capacity();
+
+    deque.clear();
+    deque.shrink_to_fit();
+
+    let line_item = Report::get_line_item(&deque);
+
+    assert_eq!(line_item.type_name, "VecDeque");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(4));
+    assert_eq!(
+        line_item.events[1],
+        ReportEvent::CapacityIncrease(initial_cap, grown_cap)
+    );
+    assert_eq!(
+        line_item.events[2],
+        ReportEvent::CapacityDecrease(grown_cap, 0)
+    );
+    assert_eq!(line_item.events.len(), 3);
+    assert_eq!(line_item.peak_len, initial_cap + 1);
+    assert_eq!(line_item.violations().len(), 1);
+}
@@ -0,0 +1,343 @@
+//! Configuration read from the environment when the report is first used, so
+//! that tracking and outputs can be changed without recompiling:
+//!
+//! | Variable                 | Values                     | Default        |
+//! |--------------------------|----------------------------|----------------|
+//! | `MAX_CAPACITY_POLICY`    | `panic`, `warn` or `off`   | `warn`         |
+//! | `MAX_CAPACITY_REPORT`    | path the report is written to by `Report::init` | stdout |
+//! | `MAX_CAPACITY_FORMAT`    | `table`, `json`, `markdown` or `html` | `table` for stdout, `json` for files |
+//! | `MAX_CAPACITY_SAMPLE`    | track one in every N collections | `1`      |
+//! | `MAX_CAPACITY_BUDGETS`   | budget file to load, see [`budget`](crate::budget) | none |
+//! | `MAX_CAPACITY_LOG`       | comma separated `kind=level`, see below | see [`LogConfig`] |
+//! | `MAX_CAPACITY_LOG_TARGET`| target events are logged under | `max_capacity` |
+//!
+//! The log kinds are `set`, `increase`, `decrease`, `spilled`, `annotation`
+//! and `exceeded`, and the levels are those of the `log` crate along with
+//! `off`, for example `MAX_CAPACITY_LOG=increase=debug,set=off`. `set`
+//! covers max bytes as well as max capacities, and `exceeded` covers every
+//! budget a collection grows past.
+//!
+//! Invalid values are logged and replaced by their default.
+//!
+//! The environment is ignored if a [`Config`] is given to `Report::configure`
+//! before the report is first used.
+
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use compact_str::CompactString;
+use log::{log, warn, Level, LevelFilter};
+use once_cell::sync::OnceCell;
+
+use crate::{Error, Report, ReportEvent, ReportFormat, Uid};
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// What to do when a collection grows past its max capacity.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Policy {
+    Panic,
+    /// Log through the `log` crate, at the `exceeded` level of the
+    /// `LogConfig`.
+    #[default]
+    Warn,
+    /// Disable tracking altogether; nothing is recorded.
+    Off,
+}
+
+impl Policy {
+    /// Applies the policy to a collection named `name` which grew past its
+    /// max capacity.
+    pub(crate) fn exceeded(self, name: &str, err: Error) {
+        match self {
+            Policy::Panic => panic!("{name}: {err}"),
+            Policy::Warn => {
+                let log_config = &Report::config().log;
+
+                if let Some(level) = log_config.exceeded {
+                    log!(target: &log_config.target, level, "{name}: {err}");
+                }
+            }
+            Policy::Off => (),
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "panic" => Ok(Policy::Panic),
+            "warn" => Ok(Policy::Warn),
+            "off" => Ok(Policy::Off),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Which events are logged through the `log` crate, and at what level. Only
+/// capacity changes, annotations and violations are logged, never every push
+/// or insert.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogConfig {
+    pub target: CompactString,
+    /// `None` disables logging of the event kind.
+    pub max_capacity_set: Option<Level>,
+    pub capacity_increase: Option<Level>,
+    pub capacity_decrease: Option<Level>,
+    pub spilled: Option<Level>,
+    pub annotation: Option<Level>,
+    /// Used by `Policy::Warn` when a collection exceeds its max capacity.
+    pub exceeded: Option<Level>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            target: "max_capacity".into(),
+            max_capacity_set: Some(Level::Debug),
+            capacity_increase: Some(Level::Info),
+            capacity_decrease: Some(Level::Info),
+            spilled: Some(Level::Info),
+            annotation: Some(Level::Info),
+            exceeded: Some(Level::Warn),
+        }
+    }
+}
+
+impl LogConfig {
+    /// The level `event` is logged at, if it's logged at all.
+    pub(crate) fn level(&self, event: &ReportEvent) -> Option<Level> {
+        match event {
+            ReportEvent::MaxCapacitySet(_) | ReportEvent::MaxBytesSet(_) => self.max_capacity_set,
+            ReportEvent::CapacityIncrease(..) => self.capacity_increase,
+            ReportEvent::CapacityDecrease(..) => self.capacity_decrease,
+            ReportEvent::Spilled(..) => self.spilled,
+            ReportEvent::Annotation(_) => self.annotation,
+            // Left to the policy, which logs them at the `exceeded` level
+            ReportEvent::LenExceeded(..)
+            | ReportEvent::BytesExceeded(..)
+            | ReportEvent::CapacityExceeded(..) => None,
+        }
+    }
+
+    /// Applies `kind=level` pairs such as `increase=debug,set=off`.
+    fn apply(&mut self, levels: &str) {
+        for pair in levels
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let parsed = pair.split_once('=').and_then(|(kind, level)| {
+                let level = level.trim().parse::<LevelFilter>().ok()?.to_level();
+                let field = match kind.trim() {
+                    "set" => &mut self.max_capacity_set,
+                    "increase" => &mut self.capacity_increase,
+                    "decrease" => &mut self.capacity_decrease,
+                    "spilled" => &mut self.spilled,
+                    "annotation" => &mut self.annotation,
+                    "exceeded" => &mut self.exceeded,
+                    _ => return None,
+                };
+
+                *field = level;
+                Some(())
+            });
+
+            if parsed.is_none() {
+                warn!("ignoring invalid MAX_CAPACITY_LOG entry {pair}");
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub policy: Policy,
+    /// Where `Report::init` emits the report to, rather than stdout.
+    pub report_path: Option<PathBuf>,
+    /// Overrides the format `Report::init` emits the report in.
+    pub format: Option<ReportFormat>,
+    /// Only one in every `sample` collections is tracked.
+    pub sample: u64,
+    /// A budget file loaded along with the config.
+    pub budgets: Option<PathBuf>,
+    pub log: LogConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            policy: Policy::default(),
+            report_path: None,
+            format: None,
+            sample: 1,
+            budgets: None,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config::from_vars(|var| env::var(var).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T: FromStr>(name: &str, value: Option<String>) -> Option<T> {
+            let value = value?;
+            let parsed = value.parse().ok();
+
+            if parsed.is_none() {
+                warn!("ignoring invalid {name}={value}");
+            }
+
+            parsed
+        }
+
+        let defaults = Config::default();
+        let mut log = defaults.log;
+
+        if let Some(target) = var("MAX_CAPACITY_LOG_TARGET") {
+            log.target = target.into();
+        }
+
+        if let Some(levels) = var("MAX_CAPACITY_LOG") {
+            log.apply(&levels);
+        }
+
+        Config {
+            policy: parse("MAX_CAPACITY_POLICY", var("MAX_CAPACITY_POLICY"))
+                .unwrap_or(defaults.policy),
+            report_path: var("MAX_CAPACITY_REPORT").map(PathBuf::from),
+            format: parse("MAX_CAPACITY_FORMAT", var("MAX_CAPACITY_FORMAT")),
+            sample: parse("MAX_CAPACITY_SAMPLE", var("MAX_CAPACITY_SAMPLE"))
+                .filter(|&sample| sample > 0)
+                .unwrap_or(defaults.sample),
+            budgets: var("MAX_CAPACITY_BUDGETS").map(PathBuf::from),
+            log,
+        }
+    }
+
+    /// Whether events for the collection `id` should be recorded.
+    pub(crate) fn is_tracked(&self, id: Uid) -> bool {
+        self.policy != Policy::Off && id.0.is_multiple_of(self.sample)
+    }
+}
+
+/// Reads the config from the environment, unless it was already configured.
+pub(crate) fn init() -> &'static Config {
+    CONFIG.get_or_init(|| load_budgets(Config::from_env()))
+}
+
+/// Loads the budget file named by `config`, if any.
+fn load_budgets(config: Config) -> Config {
+    if let Some(path) = &config.budgets {
+        if let Err(err) = Report::load_budgets(path) {
+            warn!("failed to load budget file {}: {err}", path.display());
+        }
+    }
+
+    config
+}
+
+impl Report {
+    /// The configuration in use, read from the environment unless set by
+    /// `configure`.
+    pub fn config() -> &'static Config {
+        init()
+    }
+
+    /// Uses `config` rather than reading it from the environment. This must
+    /// be called before any collection is created or the report is otherwise
+    /// used, as the config can't be changed once it is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::{Config, Error, Policy, Report};
+    ///
+    /// Report::configure(Config {
+    ///     policy: Policy::Panic,
+    ///     ..Config::default()
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(Report::config().policy, Policy::Panic);
+    /// assert!(matches!(
+    ///     Report::configure(Config::default()),
+    ///     Err(Error::AlreadyConfigured)
+    /// ));
+    /// ```
+    pub fn configure(config: Config) -> Result<(), Error> {
+        let mut config = Some(config);
+
+        CONFIG.get_or_init(|| load_budgets(config.take().expect("only taken once")));
+
+        match config {
+            Some(_) => Err(Error::AlreadyConfigured),
+            None => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_from_vars() {
+    let config = Config::from_vars(|var| {
+        Some(
+            match var {
+                "MAX_CAPACITY_POLICY" => "panic",
+                "MAX_CAPACITY_REPORT" => "report.json",
+                "MAX_CAPACITY_FORMAT" => "json",
+                "MAX_CAPACITY_SAMPLE" => "4",
+                "MAX_CAPACITY_LOG" => "increase=debug, set=off,bogus=info",
+                "MAX_CAPACITY_LOG_TARGET" => "capacity",
+                _ => return None,
+            }
+            .into(),
+        )
+    });
+
+    assert_eq!(
+        config,
+        Config {
+            policy: Policy::Panic,
+            report_path: Some("report.json".into()),
+            format: Some(ReportFormat::Json),
+            sample: 4,
+            budgets: None,
+            log: LogConfig {
+                target: "capacity".into(),
+                max_capacity_set: None,
+                capacity_increase: Some(Level::Debug),
+                ..LogConfig::default()
+            },
+        }
+    );
+    assert!(config.is_tracked(Uid(8)));
+    assert!(!config.is_tracked(Uid(9)));
+
+    let config = Config::from_vars(|var| match var {
+        "MAX_CAPACITY_POLICY" => Some("loud".into()),
+        "MAX_CAPACITY_SAMPLE" => Some("0".into()),
+        _ => None,
+    });
+
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+#[should_panic(expected = "my_map: 0x0 grew to capacity 7, exceeding its max capacity of 3")]
+fn test_panic_policy() {
+    Policy::Panic.exceeded(
+        "my_map",
+        Error::BudgetExceeded {
+            id: Uid(0),
+            limit: crate::Limit::Capacity,
+            max_capacity: 3,
+            capacity: 7,
+        },
+    );
+}
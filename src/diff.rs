@@ -0,0 +1,209 @@
+//! Compares two saved reports to find the named collections which grew
+//! between them, for example from one release to the next.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use compact_str::CompactString;
+
+use crate::LineItem;
+
+/// The totals for every line item sharing a name.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    pub peak_len: usize,
+    pub reallocations: usize,
+    pub violations: usize,
+}
+
+impl Summary {
+    /// Summarizes the named line items, grouped by name. Unnamed line items
+    /// can't be matched up between reports and so are skipped.
+    pub fn by_name(line_items: &[LineItem]) -> BTreeMap<CompactString, Summary> {
+        let mut summaries = BTreeMap::<_, Summary>::new();
+
+        for line_item in line_items.iter().filter(|line_item| line_item.is_named()) {
+            let summary = summaries
+                .entry(line_item.instance_name.clone())
+                .or_default();
+
+            summary.peak_len = summary.peak_len.max(line_item.peak_len);
+            summary.reallocations += line_item.reallocations();
+            summary.violations += line_item.violations().len();
+        }
+
+        summaries
+    }
+}
+
+/// A named collection found in both reports whose summary changed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change {
+    pub name: CompactString,
+    pub baseline: Summary,
+    pub current: Summary,
+}
+
+impl Change {
+    /// Whether the collection grew past `threshold_percent` in peak length or
+    /// reallocations, or gained any budget violations.
+    pub fn is_regression(&self, threshold_percent: f64) -> bool {
+        let grew = |baseline: usize, current: usize| {
+            current > baseline
+                && (current - baseline) as f64 > baseline as f64 * threshold_percent / 100.
+        };
+
+        grew(self.baseline.peak_len, self.current.peak_len)
+            || grew(self.baseline.reallocations, self.current.reallocations)
+            || self.current.violations > self.baseline.violations
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (baseline, current) = (self.baseline, self.current);
+
+        write!(
+            f,
+            "~ {}: peak len {} -> {}, reallocations {} -> {}, violations {} -> {}",
+            self.name,
+            baseline.peak_len,
+            current.peak_len,
+            baseline.reallocations,
+            current.reallocations,
+            baseline.violations,
+            current.violations,
+        )
+    }
+}
+
+/// The differences between the named collections of two reports.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReportDiff {
+    /// Names only found in the current report.
+    pub added: Vec<CompactString>,
+    /// Names only found in the baseline report.
+    pub removed: Vec<CompactString>,
+    /// Names whose summary changed, along with added names which already
+    /// violate their budgets, compared against an empty summary.
+    pub changed: Vec<Change>,
+}
+
+impl ReportDiff {
+    pub fn new(baseline: &[LineItem], current: &[LineItem]) -> Self {
+        let baseline = Summary::by_name(baseline);
+        let current = Summary::by_name(current);
+        let mut diff = ReportDiff::default();
+
+        for (name, &current) in &current {
+            match baseline.get(name) {
+                Some(&baseline) if baseline != current => diff.changed.push(Change {
+                    name: name.clone(),
+                    baseline,
+                    current,
+                }),
+                Some(_) => (),
+                None => {
+                    diff.added.push(name.clone());
+
+                    if current.violations > 0 {
+                        diff.changed.push(Change {
+                            name: name.clone(),
+                            baseline: Summary::default(),
+                            current,
+                        });
+                    }
+                }
+            }
+        }
+
+        diff.removed = baseline
+            .into_keys()
+            .filter(|name| !current.contains_key(name))
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The changes which count as regressions, see `Change::is_regression`.
+    pub fn regressions(&self, threshold_percent: f64) -> impl Iterator<Item = &Change> {
+        self.changed
+            .iter()
+            .filter(move |change| change.is_regression(threshold_percent))
+    }
+}
+
+impl Display for ReportDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for name in &self.added {
+            writeln!(f, "+ {name}")?;
+        }
+
+        for name in &self.removed {
+            writeln!(f, "- {name}")?;
+        }
+
+        for change in &self.changed {
+            writeln!(f, "{change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_report_diff() {
+    use crate::test_line_item as line_item;
+    use crate::ReportEvent;
+
+    let baseline = [
+        line_item("removed", 1, &[]),
+        line_item("same", 5, &[]),
+        line_item("grew", 10, &[ReportEvent::MaxCapacitySet(16)]),
+    ];
+    let current = [
+        line_item("added", 1, &[]),
+        line_item(
+            "over_budget",
+            3,
+            &[
+                ReportEvent::MaxCapacitySet(2),
+                ReportEvent::LenExceeded(2, 3),
+            ],
+        ),
+        line_item("same", 5, &[]),
+        line_item(
+            "grew",
+            20,
+            &[
+                ReportEvent::MaxCapacitySet(16),
+                ReportEvent::CapacityIncrease(16, 32),
+            ],
+        ),
+    ];
+    let diff = ReportDiff::new(&baseline, &current);
+
+    assert_eq!(diff.added, ["added", "over_budget"]);
+    assert_eq!(diff.removed, ["removed"]);
+    assert_eq!(
+        diff.to_string(),
+        "+ added\n+ over_budget\n- removed\n\
+         ~ grew: peak len 10 -> 20, reallocations 0 -> 1, violations 0 -> 1\n\
+         ~ over_budget: peak len 0 -> 3, reallocations 0 -> 0, violations 0 -> 1\n"
+    );
+
+    let regressions: Vec<_> = diff
+        .regressions(150.)
+        .map(|change| change.name.as_str())
+        .collect();
+
+    assert_eq!(regressions, ["grew", "over_budget"]);
+
+    let diff = ReportDiff::new(&baseline[..2], &baseline[..2]);
+
+    assert!(diff.is_empty());
+}
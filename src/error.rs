@@ -0,0 +1,93 @@
+use std::collections::TryReserveError;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Error as IoError;
+
+use compact_str::CompactString;
+use serde_json::Error as JsonError;
+
+use crate::{Limit, Uid};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A collection grew past the max capacity it was given, or the max
+    /// length or bytes for collections without a capacity.
+    BudgetExceeded {
+        id: Uid,
+        limit: Limit,
+        max_capacity: usize,
+        capacity: usize,
+    },
+    /// A `try_*` method refused to grow a collection past its max capacity.
+    HardCapRejected {
+        id: Uid,
+        max_capacity: usize,
+        requested: usize,
+    },
+    /// The capacity overflowed or the allocator reported a failure.
+    Reserve(TryReserveError),
+    /// The report could not be read or written.
+    Io(IoError),
+    /// A budget file could not be parsed.
+    MalformedBudgetFile { line: usize, reason: CompactString },
+    /// A saved report could not be parsed.
+    MalformedReport(JsonError),
+    /// `Report::configure` was called after the config was already in use.
+    AlreadyConfigured,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Error::BudgetExceeded {
+                id,
+                limit,
+                max_capacity,
+                capacity,
+            } => write!(
+                f,
+                "{id} grew to {limit} {capacity}, exceeding its max {limit} of {max_capacity}"
+            ),
+            Error::HardCapRejected {
+                id,
+                max_capacity,
+                requested,
+            } => write!(
+                f,
+                "{id} rejected a request for capacity {requested}, exceeding its max capacity of {max_capacity}"
+            ),
+            Error::Reserve(err) => write!(f, "failed to reserve capacity: {err}"),
+            Error::Io(err) => write!(f, "report I/O failed: {err}"),
+            Error::MalformedBudgetFile { line, reason } => {
+                write!(f, "malformed budget file on line {line}: {reason}")
+            },
+            Error::MalformedReport(err) => write!(f, "malformed report: {err}"),
+            Error::AlreadyConfigured => {
+                write!(f, "the config was already set or read from the environment")
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Reserve(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::MalformedReport(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TryReserveError> for Error {
+    fn from(err: TryReserveError) -> Self {
+        Error::Reserve(err)
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Io(err)
+    }
+}
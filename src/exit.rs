@@ -0,0 +1,214 @@
+//! Emits the report automatically when `main` returns or the program panics,
+//! rather than relying on `Report::print` being called at the right time.
+
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{markup, Error, LineItem, PrintOptions, Report};
+
+/// Guards emit the report at most once, no matter how many are dropped. The
+/// panic hook doesn't set this, as the panic may yet be caught.
+static EMITTED: AtomicBool = AtomicBool::new(false);
+
+/// How the report is rendered when emitted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportFormat {
+    /// The tables printed by `Report::print`.
+    Table,
+    /// The JSON written by `Report::write_to_file`.
+    Json,
+    /// GitHub flavored Markdown tables.
+    Markdown,
+    /// A standalone HTML page.
+    Html,
+}
+
+impl ReportFormat {
+    pub fn render(self, line_items: &[LineItem]) -> String {
+        match self {
+            ReportFormat::Table => PrintOptions::default().render(line_items),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(line_items).expect("line items always serialize")
+            }
+            ReportFormat::Markdown => markup::markdown(line_items),
+            ReportFormat::Html => markup::html(line_items),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            "markdown" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where the report is emitted to.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ReportOutput {
+    /// Print the report to stdout, as tables unless configured otherwise.
+    #[default]
+    Stdout,
+    /// Write the report to a file, as JSON unless configured otherwise.
+    File(PathBuf),
+}
+
+impl ReportOutput {
+    /// Emits the report unless a guard already has.
+    fn emit_once(&self) -> Result<(), Error> {
+        if EMITTED.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.emit()
+    }
+
+    fn emit(&self) -> Result<(), Error> {
+        let format = Report::config().format.unwrap_or(match self {
+            ReportOutput::Stdout => ReportFormat::Table,
+            ReportOutput::File(_) => ReportFormat::Json,
+        });
+        let rendered = format.render(&Report::line_items());
+
+        match self {
+            ReportOutput::Stdout => print!("{rendered}"),
+            ReportOutput::File(path) => fs::write(path, rendered)?,
+        }
+
+        Report::flush_sinks()
+    }
+
+    fn log_err(result: Result<(), Error>) {
+        if let Err(err) = result {
+            eprintln!("max_capacity: failed to emit report: {err}");
+        }
+    }
+}
+
+/// Emits the report when dropped, which includes unwinding from a panic.
+///
+/// # Examples
+///
+/// ```no_run
+/// use max_capacity::Report;
+///
+/// fn main() {
+///     let _report = Report::init();
+///
+///     // The report is printed once `_report` goes out of scope
+/// }
+/// ```
+#[must_use = "the report is emitted as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ReportGuard(ReportOutput);
+
+impl Drop for ReportGuard {
+    fn drop(&mut self) {
+        ReportOutput::log_err(self.0.emit_once());
+    }
+}
+
+impl Report {
+    /// Returns a guard which emits the report when dropped, to stdout unless
+    /// `MAX_CAPACITY_REPORT` names a file (see [`config`](crate::config)).
+    pub fn init() -> ReportGuard {
+        let output = match &Report::config().report_path {
+            Some(path) => ReportOutput::File(path.clone()),
+            None => ReportOutput::Stdout,
+        };
+
+        Report::init_with_output(output)
+    }
+
+    /// Returns a guard which emits the report to `output` when dropped.
+    pub fn init_with_output(output: ReportOutput) -> ReportGuard {
+        ReportGuard(output)
+    }
+
+    /// Emits the report to `output` when the program panics, before running
+    /// the previously installed panic hook. Unlike a `ReportGuard`, this also
+    /// works when panics abort rather than unwind.
+    ///
+    /// The report is emitted on every panic, including those which are caught
+    /// or end a thread other than main, so a `ReportGuard` still emits the
+    /// final report afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// use max_capacity::vec::Vec;
+    /// use max_capacity::{Config, Policy, Report, ReportOutput};
+    ///
+    /// let path = std::env::temp_dir().join(format!("max_capacity_{}.json", std::process::id()));
+    ///
+    /// Report::configure(Config {
+    ///     policy: Policy::Panic,
+    ///     ..Config::default()
+    /// })
+    /// .unwrap();
+    /// Report::install_panic_hook(ReportOutput::File(path.clone()));
+    ///
+    /// let mut vec = Vec::with_capacity(1).with_name("my_vec");
+    ///
+    /// vec.push(0);
+    ///
+    /// // Growing past the max capacity panics, once the report is written
+    /// assert!(panic::catch_unwind(AssertUnwindSafe(|| vec.push(1))).is_err());
+    ///
+    /// let line_items = Report::read_from_file(&path).unwrap();
+    /// std::fs::remove_file(&path).unwrap();
+    ///
+    /// assert!(line_items
+    ///     .iter()
+    ///     .any(|line_item| line_item.instance_name == "my_vec"));
+    /// ```
+    pub fn install_panic_hook(output: ReportOutput) {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            ReportOutput::log_err(output.emit());
+            previous(info);
+        }));
+    }
+}
+
+#[test]
+fn test_guard_emits_once() {
+    use crate::vec::Vec;
+    use std::fs;
+
+    let path = std::env::temp_dir().join(format!("max_capacity_{}.json", std::process::id()));
+    let vec = Vec::<u8>::with_capacity(4).with_name("exit_test_vec");
+
+    drop(Report::init_with_output(ReportOutput::File(path.clone())));
+
+    let line_items = Report::read_from_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(line_items
+        .iter()
+        .any(|line_item| line_item.instance_name == "exit_test_vec"));
+
+    drop(Report::init_with_output(ReportOutput::File(path.clone())));
+
+    assert!(!path.exists());
+
+    // The panic hook writes regardless of the guard
+    ReportOutput::File(path.clone()).emit().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    EMITTED.store(false, Ordering::SeqCst);
+    drop(vec);
+}
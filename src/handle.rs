@@ -0,0 +1,140 @@
+use crate::{Report, ReportEvent, Reportable, Uid};
+
+/// A handle to a line item of the report, for types outside of this crate to
+/// report their own capacity.
+///
+/// Holding a handle and implementing [`Reportable`] by returning its id lets
+/// a type be printed and checked along with every other collection.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::{Report, ReportHandle, Reportable, Uid};
+///
+/// struct Pool {
+///     slots: Vec<Option<u64>>,
+///     handle: ReportHandle,
+/// }
+///
+/// impl Pool {
+///     fn with_slots(slots: usize) -> Self {
+///         let handle = ReportHandle::new("Pool", slots).with_name("my_pool");
+///
+///         handle.set_max_capacity(slots, 0);
+///
+///         Pool {
+///             slots: vec![None; slots],
+///             handle,
+///         }
+///     }
+///
+///     fn grow(&mut self, additional: usize) {
+///         let current_cap = self.slots.len();
+///
+///         self.slots.resize(current_cap + additional, None);
+///         self.handle
+///             .record_capacity_change(current_cap, self.slots.len(), current_cap);
+///     }
+/// }
+///
+/// impl Reportable for Pool {
+///     fn id(&self) -> Uid {
+///         self.handle.id()
+///     }
+/// }
+///
+/// let mut pool = Pool::with_slots(4);
+///
+/// pool.grow(4);
+///
+/// assert_eq!(Report::check_collection(&pool).unwrap_err().len(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ReportHandle(Uid);
+
+impl ReportHandle {
+    /// Adds a line item for a collection of `type_name` with an initial
+    /// `capacity`.
+    pub fn new(type_name: &'static str, capacity: usize) -> Self {
+        ReportHandle(Report::new_line_item(type_name, capacity))
+    }
+
+    pub fn id(&self) -> Uid {
+        self.0
+    }
+
+    /// Appends an event to the line item and passes it along to every sink,
+    /// applying the policy if it grows the collection past its max capacity.
+    /// `len` is the length of the collection right after the event.
+    #[track_caller]
+    pub fn record(&self, event: ReportEvent, len: usize) {
+        Report::record(self.0, event, len);
+    }
+
+    /// Records the change in capacity from `previous_cap` to `capacity`, if
+    /// there is one.
+    #[track_caller]
+    pub fn record_capacity_change(&self, previous_cap: usize, capacity: usize, len: usize) {
+        Report::record_capacity_change(self.0, previous_cap, capacity, len);
+    }
+
+    /// Raises the line item's peak length to `len` if it's higher.
+    pub fn observe_len(&self, len: usize) {
+        Report::observe_len(self.0, len);
+    }
+
+    /// Sets a capacity that the collection should never grow past.
+    #[track_caller]
+    pub fn set_max_capacity(&self, max_capacity: usize, len: usize) {
+        Report::record(self.0, ReportEvent::MaxCapacitySet(max_capacity), len);
+    }
+
+    /// Names the line item. If the name has a budget, it becomes the max
+    /// capacity.
+    #[track_caller]
+    pub fn set_name(&self, name: &str, len: usize) {
+        Report::set_name(self.0, name, len);
+    }
+
+    #[track_caller]
+    pub fn with_name(self, name: &str) -> Self {
+        self.set_name(name, 0);
+        self
+    }
+
+    /// Marks a point in the collection's history, such as the start of a
+    /// phase, which is shown inline in its event table.
+    #[track_caller]
+    pub fn annotate(&self, msg: &str, len: usize) {
+        Report::record(self.0, ReportEvent::Annotation(msg.into()), len);
+    }
+}
+
+impl Reportable for ReportHandle {
+    fn id(&self) -> Uid {
+        self.0
+    }
+}
+
+#[test]
+fn test_handle_report() {
+    let handle = ReportHandle::new("Arena", 8).with_name("my_arena");
+
+    handle.set_max_capacity(8, 0);
+    handle.observe_len(6);
+    handle.record_capacity_change(8, 16, 9);
+    handle.record_capacity_change(16, 16, 12);
+
+    let line_item = Report::get_line_item(&handle);
+
+    assert_eq!(line_item.type_name, "Arena");
+    assert_eq!(line_item.instance_name, "my_arena");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(8));
+    assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(8, 16));
+    assert_eq!(line_item.events.len(), 2);
+    assert_eq!(line_item.peak_len, 9);
+    assert_eq!(line_item.capacity, 16);
+    assert!(Report::line_items()
+        .iter()
+        .any(|item| item.id == handle.id()));
+}
@@ -0,0 +1,534 @@
+//! A capacity tracking wrapper for [`hashbrown::HashMap`], enabled by the
+//! `hashbrown` feature.
+
+use hashbrown::hash_map::{
+    DefaultHashBuilder, Drain, Entry as BaseEntry, IntoIter, IntoKeys, IntoValues, Iter, IterMut,
+    Keys, Values, ValuesMut,
+};
+use hashbrown::{Equivalent, HashMap as BaseHashMap};
+
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::ops::Index;
+
+/// A `hashbrown::HashMap` which reports its capacity changes, along with its
+/// cached peak length.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::hashbrown::HashMap;
+///
+/// let mut map = HashMap::with_capacity(3).with_name("my_hashbrown_map");
+///
+/// map.insert("a", 1);
+/// assert_eq!(map[&"a"], 1);
+/// ```
+pub struct HashMap<K, V, S = DefaultHashBuilder>(BaseHashMap<K, V, S>, Tracker);
+
+// Hashbrown
+impl<K, V> HashMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty `HashMap`, which will not allocate until it is first
+    /// inserted into.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty `HashMap` which can hold at least `capacity` elements
+    /// without reallocating.
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+// Hashbrown
+impl<K, V, S> HashMap<K, V, S> {
+    /// Creates an empty `HashMap` which will use the given hash builder to
+    /// hash keys.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self(
+            BaseHashMap::with_hasher(hash_builder),
+            Tracker::new("hashbrown::HashMap", 0),
+        )
+    }
+
+    /// Creates an empty `HashMap` with the specified capacity, using
+    /// `hash_builder` to hash the keys.
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let map = BaseHashMap::with_capacity_and_hasher(capacity, hash_builder);
+        let tracker = Tracker::new("hashbrown::HashMap", map.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(capacity), 0);
+
+        Self(map, tracker)
+    }
+
+    /// Returns the number of elements the map can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.0.keys()
+    }
+
+    /// Creates a consuming iterator visiting all the keys in arbitrary order.
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        self.0.into_keys()
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.0.values()
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Creates a consuming iterator visiting all the values in arbitrary
+    /// order.
+    #[inline]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        self.0.into_values()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with
+    /// mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. Keeps
+    /// the allocated memory for reuse.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        self.1.observe_len(0);
+        self.0.drain()
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated
+    /// memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+}
+
+// Hashbrown
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Reserves capacity for at least `additional` more elements to be
+    /// inserted in the `HashMap`.
+    #[inline]
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the map with a lower limit.
+    #[inline]
+    #[track_caller]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity);
+        self.observe_capacity();
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place
+    /// manipulation.
+    #[inline]
+    #[track_caller]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        // A vacant entry can't grow the map it borrows without hiding the new
+        // capacity, so a full map makes room up front unless the key is in it
+        if self.0.len() == self.0.capacity() && !self.0.contains_key(&key) {
+            self.reserve(1);
+        }
+
+        Entry {
+            len: self.0.len(),
+            entry: self.0.entry(key),
+            tracker: &mut self.1,
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get(k)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_key_value(k)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.contains_key(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_mut(k)
+    }
+
+    /// Inserts a key-value pair into the map, returning the old value if the
+    /// key was present.
+    #[track_caller]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let ret = self.0.insert(k, v);
+        self.observe_len();
+        self.observe_capacity();
+        ret
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let ret = self.0.remove(k);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let ret = self.0.remove_entry(k);
+        self.observe_len();
+        ret
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.0.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.0.iter_mut()
+    }
+}
+
+impl<K, V, S> Clone for HashMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+}
+
+// Extra
+impl<K, V, S> HashMap<K, V, S> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+/// A view into a single entry of a [`HashMap`], which may be vacant or
+/// occupied. Inserting through it is reported like any other insert.
+pub struct Entry<'a, K, V, S> {
+    entry: BaseEntry<'a, K, V, S>,
+    tracker: &'a mut Tracker,
+    /// The length of the map before inserting into the entry.
+    len: usize,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash,
+    S: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting `default` if empty, and
+    /// returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with_key(|_| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default`
+    /// if empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Same as `or_insert_with`, but `default` is given the entry's key.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self.entry {
+            BaseEntry::Occupied(entry) => entry.into_mut(),
+            BaseEntry::Vacant(entry) => {
+                let value = default(entry.key());
+
+                self.tracker.observe_len(self.len + 1);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        Entry {
+            entry: self.entry.and_modify(f),
+            ..self
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the default value if
+    /// empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with_key(|_| V::default())
+    }
+}
+
+impl<K, V, S> Display for HashMap<K, V, S> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(
+            fmt,
+            "{name}: hashbrown::HashMap<{}, {}>",
+            type_name::<K>(),
+            type_name::<V>(),
+        )
+    }
+}
+
+// Hashbrown
+impl<K, V, S> Debug for HashMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
+    fn default() -> Self {
+        Self(
+            BaseHashMap::default(),
+            Tracker::new("hashbrown::HashMap", 0),
+        )
+    }
+}
+
+impl<K, Q: ?Sized, V, S> Index<&Q> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    Q: Hash + Equivalent<K>,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `HashMap`.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.0.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[track_caller]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = HashMap::with_hasher(Default::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    #[track_caller]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<K, V, S> Reportable for HashMap<K, V, S> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_hashbrown_report() {
+    let mut map = HashMap::<u32, ()>::with_capacity(3).with_name("my_hashbrown_map");
+
+    map.extend((0..3).map(|i| (i, ())));
+    map.entry(3).or_insert(());
+    map.clear();
+    map.shrink_to(0);
+
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(line_item.type_name, "hashbrown::HashMap");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(3));
+    assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(3, 7));
+    assert_eq!(line_item.events[2], ReportEvent::CapacityDecrease(7, 0));
+    assert_eq!(line_item.peak_len, 4);
+    assert_eq!(line_item.violations().len(), 1);
+}
@@ -0,0 +1,649 @@
+//! A capacity tracking wrapper for [`indexmap::IndexMap`], enabled by the
+//! `indexmap` feature.
+
+use indexmap::map::{
+    Drain, Entry as BaseEntry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, Values,
+    ValuesMut,
+};
+use indexmap::{Equivalent, IndexMap as BaseIndexMap};
+
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Index, RangeBounds};
+
+/// An `indexmap::IndexMap` which reports its capacity changes, along with its
+/// cached peak length.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::indexmap::IndexMap;
+///
+/// let mut map = IndexMap::with_capacity(3).with_name("my_index_map");
+///
+/// map.insert("a", 1);
+/// map.insert("b", 2);
+/// assert_eq!(map[1], 2);
+/// ```
+pub struct IndexMap<K, V, S = RandomState>(BaseIndexMap<K, V, S>, Tracker);
+
+// IndexMap
+impl<K, V> IndexMap<K, V, RandomState> {
+    /// Creates an empty `IndexMap`, which will not allocate until it is first
+    /// inserted into.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty `IndexMap` which can hold at least `capacity`
+    /// elements without reallocating.
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+// IndexMap
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Creates an empty `IndexMap` which will use the given hash builder to
+    /// hash keys.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self(
+            BaseIndexMap::with_hasher(hash_builder),
+            Tracker::new("IndexMap", 0),
+        )
+    }
+
+    /// Creates an empty `IndexMap` with the specified capacity, using
+    /// `hash_builder` to hash the keys.
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let map = BaseIndexMap::with_capacity_and_hasher(capacity, hash_builder);
+        let tracker = Tracker::new("IndexMap", map.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(capacity), 0);
+
+        Self(map, tracker)
+    }
+
+    /// Returns the number of elements the map can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order, with
+    /// mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// An iterator visiting all keys in insertion order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.0.keys()
+    }
+
+    /// Creates a consuming iterator visiting all the keys in insertion order.
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        self.0.into_keys()
+    }
+
+    /// An iterator visiting all values in insertion order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.0.values()
+    }
+
+    /// An iterator visiting all values mutably in insertion order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Creates a consuming iterator visiting all the values in insertion
+    /// order.
+    #[inline]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        self.0.into_values()
+    }
+
+    /// Removes all key-value pairs, keeping the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Shortens the map, keeping the first `len` elements and dropping the
+    /// rest.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+        self.observe_len();
+    }
+
+    /// Removes the specified range of entries from the map in bulk, returning
+    /// them as an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the
+    /// end point is greater than the length of the map.
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, K, V>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.1.observe_drain(self.0.len(), &range);
+        self.0.drain(range)
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+
+    /// Returns the key-value pair at `index`, if it's in bounds.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.0.get_index(index)
+    }
+
+    /// Returns the key and a mutable reference to the value at `index`, if
+    /// it's in bounds.
+    #[inline]
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.0.get_index_mut(index)
+    }
+
+    /// Returns the first key-value pair, if any.
+    #[inline]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.0.first()
+    }
+
+    /// Returns the last key-value pair, if any.
+    #[inline]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.0.last()
+    }
+
+    /// Removes the last key-value pair, if any.
+    #[inline]
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let ret = self.0.pop();
+        self.observe_len();
+        ret
+    }
+
+    /// Retains only the elements specified by the predicate, in order.
+    #[inline]
+    pub fn retain<F>(&mut self, keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(keep);
+        self.observe_len();
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    #[inline]
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, or as close
+    /// as the underlying table allows.
+    #[inline]
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the map with a lower limit.
+    #[inline]
+    #[track_caller]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity);
+        self.observe_capacity();
+    }
+}
+
+// IndexMap
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map, returning the old value if the
+    /// key was present. A new key is appended to the end of the map.
+    #[track_caller]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Inserts a key-value pair into the map, returning the index of the key
+    /// along with the old value if the key was present.
+    #[track_caller]
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        let ret = self.0.insert_full(key, value);
+        self.observe_len();
+        self.observe_capacity();
+        ret
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place
+    /// manipulation.
+    #[inline]
+    #[track_caller]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        // A vacant entry can't grow the map it borrows without hiding the new
+        // capacity, so a full map makes room up front unless the key is in it
+        if self.0.len() == self.0.capacity() && !self.0.contains_key(&key) {
+            self.reserve(1);
+        }
+
+        Entry {
+            len: self.0.len(),
+            entry: self.0.entry(key),
+            tracker: &mut self.1,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.contains_key(key)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get(key)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_key_value(key)
+    }
+
+    /// Returns the index, key and value corresponding to the supplied key.
+    #[inline]
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_full(key)
+    }
+
+    /// Returns the index of the supplied key, if it's present.
+    #[inline]
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_index_of(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.0.get_mut(key)
+    }
+
+    /// Removes a key from the map by swapping it with the last element,
+    /// returning its value if it was present.
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let ret = self.0.swap_remove(key);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes a key from the map by shifting all of the elements that
+    /// follow it, returning its value if it was present.
+    #[inline]
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let ret = self.0.shift_remove(key);
+        self.observe_len();
+        ret
+    }
+
+    /// Sorts the map's key-value pairs by their keys.
+    #[inline]
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.0.sort_keys()
+    }
+}
+
+impl<K, V, S> IntoIterator for IndexMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a IndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.0.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut IndexMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.0.iter_mut()
+    }
+}
+
+impl<K, V, S> Clone for IndexMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+}
+
+// Extra
+impl<K, V, S> IndexMap<K, V, S> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+/// A view into a single entry of a [`IndexMap`], which may be vacant or
+/// occupied. Inserting through it is reported like any other insert.
+pub struct Entry<'a, K, V> {
+    entry: BaseEntry<'a, K, V>,
+    tracker: &'a mut Tracker,
+    /// The length of the map before inserting into the entry.
+    len: usize,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting `default` if empty, and
+    /// returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with_key(|_| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default`
+    /// if empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// Same as `or_insert_with`, but `default` is given the entry's key.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self.entry {
+            BaseEntry::Occupied(entry) => entry.into_mut(),
+            BaseEntry::Vacant(entry) => {
+                let value = default(entry.key());
+
+                self.tracker.observe_len(self.len + 1);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Returns the index where the key-value pair exists or will be
+    /// inserted.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.entry.index()
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        Entry {
+            entry: self.entry.and_modify(f),
+            ..self
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the default value if
+    /// empty, and returns a mutable reference to the value in the entry.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with_key(|_| V::default())
+    }
+}
+
+impl<K, V, S> Display for IndexMap<K, V, S> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(
+            fmt,
+            "{name}: IndexMap<{}, {}>",
+            type_name::<K>(),
+            type_name::<V>(),
+        )
+    }
+}
+
+// IndexMap
+impl<K, V, S> Debug for IndexMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default> Default for IndexMap<K, V, S> {
+    fn default() -> Self {
+        Self(BaseIndexMap::default(), Tracker::new("IndexMap", 0))
+    }
+}
+
+impl<K, Q: ?Sized, V, S> Index<&Q> for IndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    Q: Hash + Equivalent<K>,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `IndexMap`.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        &self.0[key]
+    }
+}
+
+impl<K, V, S> Index<usize> for IndexMap<K, V, S> {
+    type Output = V;
+
+    /// Returns a reference to the value at the supplied index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &V {
+        &self.0[index]
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for IndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[track_caller]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = IndexMap::with_hasher(Default::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for IndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    #[track_caller]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<K, V, S> Reportable for IndexMap<K, V, S> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_index_map_report() {
+    let mut map = IndexMap::<u32, ()>::with_capacity(3).with_name("my_index_map");
+
+    for i in 0..3 {
+        map.insert(i, ());
+    }
+
+    map.entry(3).or_insert(());
+    map.truncate(1);
+    map.shrink_to_fit();
+
+    let line_item = Report::get_line_item(&map);
+
+    assert_eq!(line_item.type_name, "IndexMap");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(3));
+    assert!(matches!(
+        line_item.events[1].event,
+        ReportEvent::CapacityIncrease(3, _)
+    ));
+    assert!(matches!(
+        line_item.events.last().unwrap().event,
+        ReportEvent::CapacityDecrease(_, 1)
+    ));
+    assert_eq!(line_item.peak_len, 4);
+    assert_eq!(line_item.violations().len(), 1);
+}
@@ -0,0 +1,140 @@
+//! Structured `tracing` events for every report event, enabled by the
+//! `tracing` feature. Events are emitted under the `max_capacity` target at
+//! the debug level, or the warn level for events which exceed the max
+//! capacity.
+
+use compact_str::CompactString;
+use tracing::{event, Level, Span};
+
+use crate::{EventRecord, LineItem, ReportEvent};
+
+pub(crate) fn emit(
+    line_item: &LineItem,
+    record: &EventRecord,
+    budget: Option<usize>,
+    exceeded: bool,
+) {
+    let (old_cap, new_cap) = match record.event {
+        ReportEvent::MaxCapacitySet(_)
+        | ReportEvent::Annotation(_)
+        | ReportEvent::LenExceeded(..)
+        | ReportEvent::MaxBytesSet(_)
+        | ReportEvent::BytesExceeded(..)
+        | ReportEvent::CapacityExceeded(..) => (None, None),
+        ReportEvent::CapacityIncrease(old_cap, new_cap)
+        | ReportEvent::CapacityDecrease(old_cap, new_cap)
+        | ReportEvent::Spilled(old_cap, new_cap) => (Some(old_cap), Some(new_cap)),
+    };
+    let annotation = match &record.event {
+        ReportEvent::Annotation(msg) => Some(msg.as_str()),
+        _ => None,
+    };
+
+    // The level of an event must be known at compile time
+    macro_rules! emit {
+        ($level:expr) => {
+            event!(
+                target: "max_capacity",
+                $level,
+                uid = %line_item.id,
+                name = %line_item.instance_name,
+                "type" = %line_item.type_name,
+                old_cap,
+                new_cap,
+                annotation,
+                len = record.len,
+                budget,
+                location = %record.location,
+                "{}",
+                record.event.kind(),
+            )
+        };
+    }
+
+    if exceeded {
+        emit!(Level::WARN);
+    } else {
+        emit!(Level::DEBUG);
+    }
+}
+
+/// The name of the span entered on this thread, if any.
+pub(crate) fn current_span() -> Option<CompactString> {
+    Span::current()
+        .metadata()
+        .map(|metadata| metadata.name().into())
+}
+
+#[test]
+fn test_structured_fields() {
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{subscriber, Event, Metadata, Subscriber};
+
+    use crate::vec::Vec;
+    use crate::Report;
+
+    /// Captures the fields of every event as `name=value`.
+    #[derive(Default)]
+    struct Capture(Arc<Mutex<std::vec::Vec<String>>>);
+
+    struct Fields<'a>(&'a Mutex<std::vec::Vec<String>>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={value:?}", field.name()));
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut Fields(&self.0));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let capture = Capture::default();
+    let fields = capture.0.clone();
+    let vec = subscriber::with_default(capture, || {
+        let mut vec = Vec::with_capacity(1).with_name("tracing_test_vec");
+
+        vec.push(0);
+        vec.push(1);
+        vec
+    });
+    let fields = fields.lock().unwrap();
+
+    for field in [
+        "message=CapacityIncrease",
+        "name=tracing_test_vec",
+        "type=Vec",
+        "old_cap=1",
+        "len=2",
+        "budget=1",
+    ] {
+        assert!(fields.iter().any(|f| f == field), "missing {field}");
+    }
+
+    assert_eq!(Report::get_line_item(&vec).type_name, "Vec");
+}
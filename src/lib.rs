@@ -1,21 +1,66 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::io::Result as IoResult;
+use std::fs::{self, File};
+use std::io::{BufWriter, Error as IoError, Write};
+use std::panic::Location;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
 
 use compact_str::CompactString;
 use dashmap::mapref::one::{Ref, RefMut};
 use dashmap::DashMap;
 use once_cell::sync::OnceCell;
-use sealed::Reportable;
-use tabled::{Panel, Table, Tabled};
+use serde::{Deserialize, Serialize};
 
+pub mod budget;
+mod check;
 pub mod collections;
+pub mod config;
+pub mod diff;
+mod error;
+mod exit;
+mod handle;
+#[cfg(feature = "hashbrown")]
+pub mod hashbrown;
+#[cfg(feature = "indexmap")]
+pub mod indexmap;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod markup;
+pub mod metrics;
+mod print;
+mod scope;
+pub mod sink;
+#[cfg(feature = "smallvec")]
+pub mod smallvec;
+pub mod string;
+pub mod trace;
+pub mod tracked;
+mod tracker;
 pub mod vec;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub use check::{Limit, Violation, Violations};
+pub use config::{Config, LogConfig, Policy};
+pub use error::Error;
+pub use exit::{ReportFormat, ReportGuard, ReportOutput};
+pub use handle::ReportHandle;
+pub use print::{Column, PrintOptions, SortBy};
+pub use scope::ReportScope;
+pub use sink::{JsonLinesSink, ReportSink, SinkId};
+pub use tracked::{Capacity, Tracked};
+use tracker::Tracker;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Uid(u64);
 
+/// A type with a line item in the report, which can then be printed and
+/// checked along with every other collection. Types outside of this crate
+/// can implement it by holding a [`ReportHandle`].
+pub trait Reportable {
+    fn id(&self) -> Uid;
+}
+
 impl Uid {
     fn new() -> Self {
         static NEXT_UID: AtomicU64 = AtomicU64::new(0);
@@ -36,67 +81,603 @@ impl Display for Uid {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Tabled)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ReportEvent {
     /// Defines an objective limit in capacity that should not be passed;
     /// this might be the with_capacity method or even a shrink or reserve call.
     MaxCapacitySet(usize),
     CapacityIncrease(usize, usize),
     CapacityDecrease(usize, usize),
+    /// A collection with inline storage, such as a `SmallVec`, moved its
+    /// elements onto the heap, going from its inline capacity to a heap
+    /// allocated one.
+    Spilled(usize, usize),
+    /// A message marking a point in the collection's history, such as the
+    /// start of a phase, which leaves the capacity as it was.
+    Annotation(CompactString),
+    /// A collection without a capacity, such as a `BTreeMap`, grew past the
+    /// max length set by its `MaxCapacitySet`, going from the max length to
+    /// its length. This is only recorded the first time it happens.
+    LenExceeded(usize, usize),
+    /// Defines a limit in the estimated memory a collection should not grow
+    /// past, for collections without a capacity.
+    MaxBytesSet(usize),
+    /// A collection grew past its `MaxBytesSet`, going from the max bytes to
+    /// its estimated bytes. This is only recorded the first time it happens.
+    BytesExceeded(usize, usize),
+    /// A collection was named after a budget below the capacity it already
+    /// had, going from the budget to its capacity.
+    CapacityExceeded(usize, usize),
+}
+
+impl ReportEvent {
+    /// The name of the event's variant.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ReportEvent::MaxCapacitySet(_) => "MaxCapacitySet",
+            ReportEvent::CapacityIncrease(..) => "CapacityIncrease",
+            ReportEvent::CapacityDecrease(..) => "CapacityDecrease",
+            ReportEvent::Spilled(..) => "Spilled",
+            ReportEvent::Annotation(_) => "Annotation",
+            ReportEvent::LenExceeded(..) => "LenExceeded",
+            ReportEvent::MaxBytesSet(_) => "MaxBytesSet",
+            ReportEvent::BytesExceeded(..) => "BytesExceeded",
+            ReportEvent::CapacityExceeded(..) => "CapacityExceeded",
+        }
+    }
+}
+
+/// A `ReportEvent` along with the state of the collection when it happened.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventRecord {
+    pub event: ReportEvent,
+    /// The length of the collection right after the event.
+    pub len: usize,
+    #[serde(rename = "timestamp_us", with = "micros")]
+    pub timestamp: SystemTime,
+    /// The `file:line:column` of the call which triggered the event.
+    pub location: CompactString,
+}
+
+impl EventRecord {
+    #[track_caller]
+    fn new(event: ReportEvent, len: usize) -> Self {
+        EventRecord {
+            event,
+            len,
+            timestamp: SystemTime::now(),
+            location: caller_location(),
+        }
+    }
+}
+
+/// The `file:line:column` of the caller.
+#[track_caller]
+fn caller_location() -> CompactString {
+    let location = Location::caller();
+
+    compact_str::format_compact!(
+        "{}:{}:{}",
+        location.file(),
+        location.line(),
+        location.column()
+    )
+}
+
+/// A point in the history of the whole program, such as the start of a
+/// phase, marked by `Report::annotate`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Annotation {
+    pub msg: CompactString,
+    #[serde(rename = "timestamp_us", with = "micros")]
+    pub timestamp: SystemTime,
+    /// The `file:line:column` of the call to `Report::annotate`.
+    pub location: CompactString,
 }
 
-#[derive(Clone, Default, Debug)]
+impl PartialEq<ReportEvent> for EventRecord {
+    fn eq(&self, other: &ReportEvent) -> bool {
+        self.event == *other
+    }
+}
+
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
 pub struct LineItem {
     pub id: Uid,
     pub instance_name: CompactString,
-    pub events: Vec<ReportEvent>,
+    /// The kind of collection, such as `Vec` or `HashMap`.
+    #[serde(default)]
+    pub type_name: CompactString,
+    pub events: Vec<EventRecord>,
+    /// The highest length the collection has been observed at.
+    pub peak_len: usize,
+    /// The capacity as of the latest capacity change.
+    #[serde(default)]
+    pub capacity: usize,
+    /// The current length, added up over the collection and its clones.
+    #[serde(default)]
+    pub len: usize,
+    /// Whether the collection and all of its clones were dropped.
+    #[serde(default)]
+    pub dropped: bool,
+    /// The `tracing` span the collection was created in, when the `tracing`
+    /// feature is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<CompactString>,
 }
 
+const UNNAMED: &str = "Unnamed";
+
 impl LineItem {
-    fn print(&self) {
-        let mut table = Table::new(&self.events);
-        table.with(Panel::header(format!(
-            "{} - {}",
-            self.id, self.instance_name
-        )));
+    fn new(id: Uid) -> Self {
+        LineItem {
+            id,
+            instance_name: UNNAMED.into(),
+            type_name: CompactString::default(),
+            events: Vec::new(),
+            peak_len: 0,
+            capacity: 0,
+            len: 0,
+            dropped: false,
+            span: None,
+        }
+    }
+
+    /// A copy of everything but the events, which is cheap to make.
+    fn without_events(&self) -> Self {
+        LineItem {
+            id: self.id,
+            instance_name: self.instance_name.clone(),
+            type_name: self.type_name.clone(),
+            events: Vec::new(),
+            peak_len: self.peak_len,
+            capacity: self.capacity,
+            len: self.len,
+            dropped: self.dropped,
+            span: self.span.clone(),
+        }
+        .with_published()
+    }
+
+    /// Updates the length and peak length to those published by the
+    /// collection's trackers, which aren't written to the report on every
+    /// push.
+    fn with_published(mut self) -> Self {
+        if let Some(live) = tracker::published(self.id) {
+            self.len = live.len;
+            self.peak_len = self.peak_len.max(live.peak_len).max(live.len);
+        }
+
+        self
+    }
+
+    /// A line item with nothing recorded, which keeps the type and capacity
+    /// of the collection if it's still alive.
+    fn restored(id: Uid) -> Self {
+        let mut line_item = LineItem::new(id);
+
+        if let Some(live) = tracker::published(id) {
+            line_item.type_name = live.type_name.into();
+            line_item.capacity = live.capacity;
+        }
+
+        line_item
+    }
+
+    /// Appends a record, updating the length, peak length and capacity to
+    /// match.
+    fn push(&mut self, record: EventRecord) {
+        if let ReportEvent::CapacityIncrease(_, capacity)
+        | ReportEvent::CapacityDecrease(_, capacity)
+        | ReportEvent::Spilled(_, capacity) = record.event
+        {
+            self.capacity = capacity;
+        }
+
+        self.len = record.len;
+        self.peak_len = self.peak_len.max(record.len);
+        self.events.push(record);
+    }
+
+    /// How many times the collection had to grow its capacity, including
+    /// spilling onto the heap.
+    pub fn reallocations(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|record| {
+                matches!(
+                    record.event,
+                    ReportEvent::CapacityIncrease(..) | ReportEvent::Spilled(..)
+                )
+            })
+            .count()
+    }
+
+    /// The highest capacity the collection has been observed at, whether
+    /// initially or after a capacity change.
+    pub fn peak_capacity(&self) -> usize {
+        self.events
+            .iter()
+            .filter_map(|record| match record.event {
+                ReportEvent::CapacityIncrease(_, capacity) | ReportEvent::Spilled(_, capacity) => {
+                    Some(capacity)
+                }
+                ReportEvent::CapacityDecrease(capacity, _) => Some(capacity),
+                _ => None,
+            })
+            .fold(self.capacity, usize::max)
+    }
+
+    /// Whether the collection was given a name via `set_name` or `with_name`.
+    pub fn is_named(&self) -> bool {
+        self.instance_name != UNNAMED
+    }
+
+    /// Whether budgets were loaded but none was found for this collection's name.
+    pub fn is_unbudgeted(&self) -> bool {
+        self.is_named() && budget::is_unbudgeted(&self.instance_name)
+    }
+
+    /// The most recently set max capacity, if any.
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.events
+            .iter()
+            .rev()
+            .find_map(|record| match record.event {
+                ReportEvent::MaxCapacitySet(cap) => Some(cap),
+                _ => None,
+            })
+    }
+}
 
-        println!("{table}");
+impl Display for LineItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", print::event_table(self))
     }
 }
 
 static REPORT_DATA: OnceCell<DashMap<Uid, LineItem>> = OnceCell::new();
 
+/// Global annotations, in the order they were made.
+static ANNOTATIONS: Mutex<Vec<Annotation>> = Mutex::new(Vec::new());
+
+/// The report data, reading the config on first use.
+fn report_data() -> &'static DashMap<Uid, LineItem> {
+    REPORT_DATA.get_or_init(|| {
+        config::init();
+        DashMap::new()
+    })
+}
+
 pub struct Report;
 
 impl Report {
-    pub(crate) fn new_line_item() -> Uid {
+    /// Adds a line item for a collection of `type_name` with an initial
+    /// `capacity`.
+    pub(crate) fn new_line_item(type_name: &'static str, capacity: usize) -> Uid {
         let id = Uid::new();
-        REPORT_DATA.get_or_init(DashMap::new).insert(
+
+        if Report::config().is_tracked(id) {
+            let line_item = LineItem {
+                type_name: type_name.into(),
+                capacity,
+                #[cfg(feature = "tracing")]
+                span: instrument::current_span(),
+                ..LineItem::new(id)
+            };
+
+            report_data().insert(id, line_item);
+            scope::register(id);
+        }
+
+        id
+    }
+
+    /// Appends an event to a line item and passes it along to every sink.
+    /// `len` is the length of the collection right after the event.
+    #[track_caller]
+    pub(crate) fn record(id: Uid, event: ReportEvent, len: usize) {
+        let config = Report::config();
+
+        if !config.is_tracked(id) {
+            return;
+        }
+
+        let mut entry = Report::get_mut(id);
+        let exceeded = match (&event, entry.max_capacity()) {
+            (
+                &ReportEvent::CapacityIncrease(_, capacity) | &ReportEvent::Spilled(_, capacity),
+                Some(max_capacity),
+            ) if capacity > max_capacity => Some((Limit::Capacity, max_capacity, capacity)),
+            (&ReportEvent::LenExceeded(max_len, len), _) => Some((Limit::Len, max_len, len)),
+            (&ReportEvent::CapacityExceeded(max_capacity, capacity), _) => {
+                Some((Limit::Capacity, max_capacity, capacity))
+            }
+            (&ReportEvent::BytesExceeded(max_bytes, bytes), _) => {
+                Some((Limit::Bytes, max_bytes, bytes))
+            }
+            _ => None,
+        }
+        .map(|(limit, max_capacity, capacity)| Error::BudgetExceeded {
             id,
-            LineItem {
+            limit,
+            max_capacity,
+            capacity,
+        });
+
+        entry.push(EventRecord::new(event, len));
+
+        // Sinks, logging and the policy all run with the line item unlocked,
+        // so they only get a copy of it without its events
+        let record = entry.events.last().expect("event was just pushed").clone();
+        #[cfg(feature = "tracing")]
+        let budget = entry.max_capacity();
+        let line_item = entry.without_events();
+
+        drop(entry);
+
+        sink::dispatch(id, &line_item, &record);
+
+        if let Some(level) = config.log.level(&record.event) {
+            log::log!(
+                target: &config.log.target,
+                level,
+                "{}",
+                sink::format_event(id, &line_item, &record)
+            );
+        }
+
+        #[cfg(feature = "tracing")]
+        instrument::emit(&line_item, &record, budget, exceeded.is_some());
+
+        if let Some(err) = exceeded {
+            config.policy.exceeded(&line_item.instance_name, err);
+        }
+    }
+
+    /// Marks that a tracker of a line item was dropped, raising its peak
+    /// length to the tracker's `peak_len`. Once the `last` one is gone the
+    /// line item is marked dropped, and its length is zero.
+    pub(crate) fn retire(id: Uid, peak_len: usize, last: bool) {
+        if !Report::config().is_tracked(id) {
+            return;
+        }
+
+        // A reset may have cleared the line item, which shouldn't come back
+        if let Some(mut line_item) = report_data().get_mut(&id) {
+            line_item.peak_len = line_item.peak_len.max(peak_len);
+
+            if last {
+                line_item.len = 0;
+                line_item.dropped = true;
+            }
+        }
+    }
+
+    /// Raises a line item's peak length to `len` if it's higher.
+    pub(crate) fn observe_len(id: Uid, len: usize) {
+        if !Report::config().is_tracked(id) {
+            return;
+        }
+
+        let mut line_item = Report::get_mut(id);
+        line_item.peak_len = line_item.peak_len.max(len);
+    }
+
+    /// Records the change in capacity from `previous_cap` to `capacity`, if
+    /// there is one. `len` is the length of the collection right after it.
+    #[track_caller]
+    pub(crate) fn record_capacity_change(
+        id: Uid,
+        previous_cap: usize,
+        capacity: usize,
+        len: usize,
+    ) {
+        if capacity > previous_cap {
+            Report::record(
                 id,
-                instance_name: "Unnamed".into(),
-                events: Vec::new(),
-            },
-        );
-        id
+                ReportEvent::CapacityIncrease(previous_cap, capacity),
+                len,
+            );
+        } else if capacity < previous_cap {
+            Report::record(
+                id,
+                ReportEvent::CapacityDecrease(previous_cap, capacity),
+                len,
+            );
+        }
+    }
+
+    /// Names a line item. If the name has a budget, it becomes the max capacity.
+    #[track_caller]
+    pub(crate) fn set_name(id: Uid, name: &str, len: usize) {
+        if !Report::config().is_tracked(id) {
+            return;
+        }
+
+        Report::get_mut(id).instance_name = name.into();
+
+        if let Some(budget) = budget::lookup(name) {
+            Report::record(id, ReportEvent::MaxCapacitySet(budget), len);
+
+            let capacity = Report::get(id).capacity;
+
+            if capacity > budget {
+                Report::record(id, ReportEvent::CapacityExceeded(budget, capacity), len);
+            }
+        }
+    }
+
+    /// Marks a point in the history of the whole program, such as the start
+    /// of a phase. Annotations are kept on their own timeline rather than
+    /// added to each line item, and show up in the event table of every
+    /// collection with events on both sides of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::vec::Vec;
+    /// use max_capacity::Report;
+    ///
+    /// let mut vec = Vec::with_capacity(1).with_name("my_vec");
+    ///
+    /// vec.push(0);
+    /// Report::annotate("warmup done");
+    /// vec.push(1);
+    ///
+    /// assert_eq!(Report::annotations()[0].msg, "warmup done");
+    /// assert_eq!(Report::get_line_item(&vec).events.len(), 2);
+    /// assert!(Report::get_line_item(&vec)
+    ///     .to_string()
+    ///     .contains("# warmup done"));
+    /// ```
+    #[track_caller]
+    pub fn annotate(msg: &str) {
+        let config = Report::config();
+
+        if config.policy == Policy::Off {
+            return;
+        }
+
+        let annotation = Annotation {
+            msg: msg.into(),
+            timestamp: SystemTime::now(),
+            location: caller_location(),
+        };
+
+        if let Some(level) = config.log.annotation {
+            log::log!(
+                target: &config.log.target,
+                level,
+                "# {} at {}",
+                annotation.msg,
+                annotation.location
+            );
+        }
+
+        ANNOTATIONS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(annotation);
+    }
+
+    /// Every global annotation, in the order they were made.
+    pub fn annotations() -> Vec<Annotation> {
+        ANNOTATIONS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
     }
 
     pub fn print() {
-        for ref_multi in REPORT_DATA.get_or_init(DashMap::new).iter() {
-            let (_id, line_item) = ref_multi.pair();
-            line_item.print()
+        Report::print_with(&PrintOptions::default())
+    }
+
+    /// Writes every line item to `path` as JSON, which can be loaded back
+    /// with `read_from_file`.
+    pub fn write_to_file(path: &Path) -> Result<(), Error> {
+        Report::write_line_items(path, &Report::line_items())
+    }
+
+    /// Loads the line items of a report saved by `write_to_file`, or of the
+    /// events streamed to a file by a `JsonLinesSink`.
+    pub fn read_from_file(path: &Path) -> Result<Vec<LineItem>, Error> {
+        let contents = fs::read_to_string(path)?;
+
+        if contents.trim_start().starts_with('[') {
+            serde_json::from_str(&contents).map_err(Error::MalformedReport)
+        } else {
+            sink::read_json_lines(&contents)
+        }
+    }
+
+    /// Takes a snapshot of every line item in the report, ordered by `Uid`.
+    pub fn line_items() -> Vec<LineItem> {
+        let config = Report::config();
+        let mut line_items: Vec<_> = report_data()
+            .iter()
+            .filter(|ref_multi| config.is_tracked(*ref_multi.key()))
+            .map(|ref_multi| ref_multi.value().clone().with_published())
+            .collect();
+
+        line_items.sort_by_key(|line_item| line_item.id);
+        line_items
+    }
+
+    /// Clears every line item and annotation from the report. Collections
+    /// which are still alive start over with an unnamed, empty line item of
+    /// the same type, whose peak length starts from their length at the
+    /// reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use max_capacity::vec::Vec;
+    /// use max_capacity::{Report, ReportScope};
+    ///
+    /// let scope = ReportScope::new();
+    /// let mut vec = Vec::<u8>::with_capacity(1).with_name("my_vec");
+    ///
+    /// for i in 0..10 {
+    ///     vec.push(i);
+    /// }
+    ///
+    /// while vec.pop().is_some() {}
+    /// drop(Vec::<u8>::new());
+    /// Report::reset();
+    ///
+    /// // The dropped vec is gone for good
+    /// assert_eq!(Report::line_items().len(), 1);
+    /// assert_eq!(scope.line_items().len(), 1);
+    /// assert!(Report::get_line_item(&vec).events.is_empty());
+    ///
+    /// for i in 0..5 {
+    ///     vec.push(i);
+    /// }
+    ///
+    /// let line_item = Report::get_line_item(&vec);
+    ///
+    /// assert_eq!(line_item.instance_name, "Unnamed");
+    /// assert_eq!(line_item.type_name, "Vec");
+    /// assert_eq!(line_item.peak_len, 5);
+    /// assert_eq!(line_item.capacity, vec.capacity());
+    /// ```
+    pub fn reset() {
+        if let Some(report_data) = REPORT_DATA.get() {
+            report_data.clear();
+
+            for id in tracker::reset_published() {
+                report_data.insert(id, LineItem::restored(id));
+            }
         }
+
+        ANNOTATIONS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
     }
 
-    pub fn write_to_file(_path: &Path) -> IoResult<()> {
-        unimplemented!()
+    pub(crate) fn write_line_items(path: &Path, line_items: &[LineItem]) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer_pretty(&mut writer, line_items).map_err(IoError::from)?;
+        writer.flush()?;
+
+        Ok(())
     }
 
     pub fn get_line_item<R: Reportable>(reportable: &R) -> LineItem {
         // We do a clone here because we don't want to risk "leaking" a reference
         // to the caller which could block further writes if it doesn't get dropped
-        Report::get(reportable.id()).clone()
+        Report::get(reportable.id()).clone().with_published()
+    }
+
+    /// Takes a snapshot of a line item, unlike `get` without adding it if it
+    /// is missing.
+    pub(crate) fn find(id: Uid) -> Option<LineItem> {
+        report_data()
+            .get(&id)
+            .map(|line_item| line_item.clone().with_published())
     }
 
     /// This function (and uses of the return value) must never be made public
@@ -108,14 +689,49 @@ impl Report {
     /// This function (and uses of the return value) must never be made public
     /// or else it risks deadlocking
     pub(crate) fn get_mut(id: Uid) -> RefMut<'static, Uid, LineItem> {
-        REPORT_DATA.get_or_init(DashMap::new).entry(id).or_default()
+        report_data()
+            .entry(id)
+            .or_insert_with(|| LineItem::restored(id))
     }
 }
 
-mod sealed {
-    use super::Uid;
+/// (De)serializes a `SystemTime` as microseconds since the unix epoch.
+mod micros {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
 
-    pub trait Reportable {
-        fn id(&self) -> Uid;
+        serializer.serialize_u64(micros as u64)
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let micros = u64::deserialize(deserializer)?;
+
+        Ok(UNIX_EPOCH + Duration::from_micros(micros))
+    }
+}
+
+/// A line item named `name` with `events` recorded at a len of zero, for
+/// tests which render or compare line items without real collections. The
+/// events are located at the caller.
+#[cfg(test)]
+#[track_caller]
+pub(crate) fn test_line_item(name: &str, peak_len: usize, events: &[ReportEvent]) -> LineItem {
+    let mut line_item = LineItem {
+        instance_name: name.into(),
+        ..LineItem::new(Uid::new())
+    };
+
+    for event in events {
+        line_item.push(EventRecord::new(event.clone(), 0));
+    }
+
+    line_item.peak_len = peak_len;
+    line_item
 }
@@ -0,0 +1,189 @@
+//! Renders the report as Markdown or HTML, for pasting into PR comments and
+//! wiki pages. Collections which exceeded their max capacity are highlighted,
+//! as are the events which pushed them over it.
+
+use std::fmt::Write;
+
+use tabled::builder::Builder;
+use tabled::Style;
+
+use crate::print::{event_rows, heading, EVENT_COLUMNS};
+use crate::LineItem;
+
+const HTML_HEADER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Capacity report</title>
+<style>
+body { font-family: sans-serif; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+caption { font-weight: bold; text-align: left; padding: 0.25em 0; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+table.violating caption { color: #b00; }
+tr.exceeded td { background: #fdd; }
+</style>
+</head>
+<body>
+"#;
+
+const HTML_FOOTER: &str = "</body>\n</html>\n";
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`' | '<' | '[' | ']') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// One GitHub flavored table per line item. Violating line items are marked
+/// in their heading and followed by their violations as a diff.
+pub(crate) fn markdown(line_items: &[LineItem]) -> String {
+    let mut rendered = String::new();
+
+    for line_item in line_items {
+        let violations = line_item.violations();
+        let heading = escape_markdown(&heading(line_item));
+
+        if violations.is_empty() {
+            let _ = writeln!(rendered, "### {heading}\n");
+        } else {
+            let _ = writeln!(rendered, "### :warning: {heading}\n\n```diff");
+
+            for violation in &violations {
+                let _ = writeln!(rendered, "{violation}");
+            }
+
+            let _ = writeln!(rendered, "```\n");
+        }
+
+        let mut builder = Builder::default();
+
+        builder.set_columns(EVENT_COLUMNS);
+
+        for (cells, exceeded) in event_rows(line_item) {
+            builder.add_record(cells.map(|cell| match exceeded {
+                true => format!("**{}**", escape_markdown(&cell)),
+                false => escape_markdown(&cell),
+            }));
+        }
+
+        let mut table = builder.build();
+
+        table.with(Style::markdown());
+
+        let _ = writeln!(rendered, "{table}\n");
+    }
+
+    rendered
+}
+
+/// A standalone HTML page with one table per line item.
+pub(crate) fn html(line_items: &[LineItem]) -> String {
+    let mut rendered = String::from(HTML_HEADER);
+
+    for line_item in line_items {
+        let violations = line_item.violations().len();
+
+        if violations == 0 {
+            rendered.push_str("<table>\n");
+            let _ = writeln!(
+                rendered,
+                "<caption>{}</caption>",
+                escape_html(&heading(line_item))
+            );
+        } else {
+            rendered.push_str("<table class=\"violating\">\n");
+            let _ = writeln!(
+                rendered,
+                "<caption>{} ({violations} violation(s))</caption>",
+                escape_html(&heading(line_item))
+            );
+        }
+
+        rendered.push_str("<thead><tr>");
+
+        for column in EVENT_COLUMNS {
+            let _ = write!(rendered, "<th>{column}</th>");
+        }
+
+        rendered.push_str("</tr></thead>\n<tbody>\n");
+
+        for (cells, exceeded) in event_rows(line_item) {
+            rendered.push_str(match exceeded {
+                true => "<tr class=\"exceeded\">",
+                false => "<tr>",
+            });
+
+            for cell in cells {
+                let _ = write!(rendered, "<td>{}</td>", escape_html(&cell));
+            }
+
+            rendered.push_str("</tr>\n");
+        }
+
+        rendered.push_str("</tbody>\n</table>\n");
+    }
+
+    rendered.push_str(HTML_FOOTER);
+    rendered
+}
+
+#[test]
+fn test_markup_highlights_violations() {
+    use crate::test_line_item as line_item;
+    use crate::ReportEvent;
+
+    let line_items = [
+        line_item(
+            "my_map",
+            0,
+            &[
+                ReportEvent::MaxCapacitySet(16),
+                ReportEvent::CapacityIncrease(16, 32),
+            ],
+        ),
+        line_item("a|b", 0, &[ReportEvent::MaxCapacitySet(4)]),
+    ];
+
+    let rendered = markdown(&line_items);
+
+    assert!(rendered.contains(":warning:"));
+    assert!(rendered.contains("my\\_map"));
+    assert!(rendered.contains("**16 -> 32**"));
+    assert!(rendered.contains("a\\|b"));
+    assert!(rendered.contains("|---"));
+
+    let rendered = html(&line_items);
+
+    assert!(rendered.starts_with("<!DOCTYPE html>"));
+    assert!(rendered.contains("<table class=\"violating\">"));
+    assert!(rendered.contains("(1 violation(s))"));
+    assert!(
+        rendered.contains("<tr class=\"exceeded\"><td>CapacityIncrease</td><td>16 -&gt; 32</td>")
+    );
+    assert!(rendered.ends_with("</html>\n"));
+}
@@ -0,0 +1,220 @@
+//! Exposes the report in the Prometheus text format, so that a service can
+//! serve it from its own metrics endpoint:
+//!
+//! ```text
+//! # HELP max_capacity_capacity Capacity as of the latest capacity change, of collections still alive.
+//! # TYPE max_capacity_capacity gauge
+//! max_capacity_capacity{name="my_map",type="HashMap"} 7
+//! ```
+//!
+//! Only named collections are exposed. Collections sharing a name and type
+//! are added together, except for their peak and max capacity which are the
+//! highest of the group.
+//!
+//! Line items outlive their collections until `Report::reset` is called, but
+//! the length and capacity of a dropped collection are no longer added to its
+//! group.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use compact_str::CompactString;
+
+use crate::{LineItem, Report};
+
+#[derive(Default)]
+struct Sample {
+    len: usize,
+    peak_len: usize,
+    capacity: usize,
+    max_capacity: Option<usize>,
+    reallocations: usize,
+    violations: usize,
+}
+
+struct Metric {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    value: fn(&Sample) -> Option<usize>,
+}
+
+const METRICS: [Metric; 6] = [
+    Metric {
+        name: "max_capacity_len",
+        kind: "gauge",
+        help: "Current length.",
+        value: |sample| Some(sample.len),
+    },
+    Metric {
+        name: "max_capacity_peak_len",
+        kind: "gauge",
+        help: "Highest length observed.",
+        value: |sample| Some(sample.peak_len),
+    },
+    Metric {
+        name: "max_capacity_capacity",
+        kind: "gauge",
+        help: "Capacity as of the latest capacity change, of collections still alive.",
+        value: |sample| Some(sample.capacity),
+    },
+    Metric {
+        name: "max_capacity_max_capacity",
+        kind: "gauge",
+        help: "Capacity which should not be exceeded.",
+        value: |sample| sample.max_capacity,
+    },
+    Metric {
+        name: "max_capacity_reallocations_total",
+        kind: "counter",
+        help: "Times the capacity had to grow.",
+        value: |sample| Some(sample.reallocations),
+    },
+    Metric {
+        name: "max_capacity_violations_total",
+        kind: "counter",
+        help: "Times the capacity grew past its max capacity.",
+        value: |sample| Some(sample.violations),
+    },
+];
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub(crate) fn render(line_items: &[LineItem]) -> String {
+    let mut samples = BTreeMap::<(CompactString, CompactString), Sample>::new();
+
+    for line_item in line_items.iter().filter(|line_item| line_item.is_named()) {
+        let key = (line_item.instance_name.clone(), line_item.type_name.clone());
+        let sample = samples.entry(key).or_default();
+
+        sample.peak_len = sample.peak_len.max(line_item.peak_len);
+
+        if !line_item.dropped {
+            sample.len += line_item.len;
+            sample.capacity += line_item.capacity;
+        }
+
+        sample.max_capacity = sample.max_capacity.max(line_item.max_capacity());
+        sample.reallocations += line_item.reallocations();
+        sample.violations += line_item.violations().len();
+    }
+
+    let mut rendered = String::new();
+
+    for metric in &METRICS {
+        let _ = writeln!(rendered, "# HELP {} {}", metric.name, metric.help);
+        let _ = writeln!(rendered, "# TYPE {} {}", metric.name, metric.kind);
+
+        for ((name, type_name), sample) in &samples {
+            if let Some(value) = (metric.value)(sample) {
+                let _ = writeln!(
+                    rendered,
+                    "{}{{name=\"{}\",type=\"{}\"}} {value}",
+                    metric.name,
+                    escape_label(name),
+                    escape_label(type_name)
+                );
+            }
+        }
+    }
+
+    rendered
+}
+
+impl Report {
+    /// Renders every named collection in the Prometheus text format. See the
+    /// [`metrics`](crate::metrics) module for details.
+    pub fn render_prometheus() -> String {
+        render(&Report::line_items())
+    }
+}
+
+#[test]
+fn test_render() {
+    use crate::{ReportEvent, Uid};
+
+    let line_item = |name, peak_len, events: &[ReportEvent]| LineItem {
+        type_name: "HashMap".into(),
+        ..crate::test_line_item(name, peak_len, events)
+    };
+    let line_items = [
+        line_item(
+            "my_map",
+            5,
+            &[
+                ReportEvent::MaxCapacitySet(4),
+                ReportEvent::CapacityIncrease(4, 8),
+            ],
+        ),
+        line_item("my_map", 1, &[ReportEvent::CapacityDecrease(4, 2)]),
+        LineItem {
+            dropped: true,
+            ..line_item("my_map", 9, &[ReportEvent::CapacityIncrease(0, 16)])
+        },
+        line_item("say \"hi\"", 0, &[]),
+        LineItem::new(Uid::new()),
+    ];
+
+    assert_eq!(
+        render(&line_items),
+        "\
+# HELP max_capacity_len Current length.
+# TYPE max_capacity_len gauge
+max_capacity_len{name=\"my_map\",type=\"HashMap\"} 0
+max_capacity_len{name=\"say \\\"hi\\\"\",type=\"HashMap\"} 0
+# HELP max_capacity_peak_len Highest length observed.
+# TYPE max_capacity_peak_len gauge
+max_capacity_peak_len{name=\"my_map\",type=\"HashMap\"} 9
+max_capacity_peak_len{name=\"say \\\"hi\\\"\",type=\"HashMap\"} 0
+# HELP max_capacity_capacity Capacity as of the latest capacity change, of collections still alive.
+# TYPE max_capacity_capacity gauge
+max_capacity_capacity{name=\"my_map\",type=\"HashMap\"} 10
+max_capacity_capacity{name=\"say \\\"hi\\\"\",type=\"HashMap\"} 0
+# HELP max_capacity_max_capacity Capacity which should not be exceeded.
+# TYPE max_capacity_max_capacity gauge
+max_capacity_max_capacity{name=\"my_map\",type=\"HashMap\"} 4
+# HELP max_capacity_reallocations_total Times the capacity had to grow.
+# TYPE max_capacity_reallocations_total counter
+max_capacity_reallocations_total{name=\"my_map\",type=\"HashMap\"} 2
+max_capacity_reallocations_total{name=\"say \\\"hi\\\"\",type=\"HashMap\"} 0
+# HELP max_capacity_violations_total Times the capacity grew past its max capacity.
+# TYPE max_capacity_violations_total counter
+max_capacity_violations_total{name=\"my_map\",type=\"HashMap\"} 1
+max_capacity_violations_total{name=\"say \\\"hi\\\"\",type=\"HashMap\"} 0
+"
+    );
+}
+
+#[test]
+fn test_len_gauge() {
+    use crate::vec::Vec;
+
+    let gauge = |metric: &str| {
+        let prefix = format!("{metric}{{name=\"len_gauge_vec\",type=\"Vec\"}} ");
+
+        Report::render_prometheus()
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix)?.parse::<usize>().ok())
+    };
+    let mut vec = Vec::with_capacity(4).with_name("len_gauge_vec");
+
+    vec.push(0);
+    vec.push(1);
+    vec.push(2);
+    vec.pop();
+
+    assert_eq!(gauge("max_capacity_len"), Some(2));
+    assert_eq!(gauge("max_capacity_peak_len"), Some(3));
+    assert_eq!(gauge("max_capacity_capacity"), Some(4));
+
+    drop(vec);
+
+    assert_eq!(gauge("max_capacity_len"), Some(0));
+    assert_eq!(gauge("max_capacity_peak_len"), Some(3));
+    assert_eq!(gauge("max_capacity_capacity"), Some(0));
+}
@@ -0,0 +1,420 @@
+//! Renders the report as tables, with control over which line items are
+//! shown, their order and whether to summarize them.
+
+use std::cmp::Reverse;
+use std::io::Write;
+
+use compact_str::CompactString;
+use tabled::builder::Builder;
+use tabled::{Panel, Table};
+
+use crate::{Annotation, Error, LineItem, Report, ReportEvent};
+
+/// The order line items are rendered in. Ties are broken by `Uid`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortBy {
+    /// Creation order.
+    #[default]
+    Uid,
+    Name,
+    /// Highest peak length first.
+    PeakLen,
+    /// Most reallocations first.
+    Reallocations,
+}
+
+/// A column of the summary table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Column {
+    Uid,
+    Name,
+    MaxCapacity,
+    PeakLen,
+    Reallocations,
+    Violations,
+}
+
+impl Column {
+    pub const ALL: [Column; 6] = [
+        Column::Uid,
+        Column::Name,
+        Column::MaxCapacity,
+        Column::PeakLen,
+        Column::Reallocations,
+        Column::Violations,
+    ];
+
+    fn header(self) -> &'static str {
+        match self {
+            Column::Uid => "uid",
+            Column::Name => "name",
+            Column::MaxCapacity => "max capacity",
+            Column::PeakLen => "peak len",
+            Column::Reallocations => "reallocations",
+            Column::Violations => "violations",
+        }
+    }
+
+    fn cell(self, line_item: &LineItem) -> String {
+        match self {
+            Column::Uid => line_item.id.to_string(),
+            Column::Name => line_item.instance_name.to_string(),
+            Column::MaxCapacity => line_item
+                .max_capacity()
+                .map_or_else(|| "-".into(), |cap| cap.to_string()),
+            Column::PeakLen => line_item.peak_len.to_string(),
+            Column::Reallocations => line_item.reallocations().to_string(),
+            Column::Violations => line_item.violations().len().to_string(),
+        }
+    }
+}
+
+/// Controls how `Report::print_with` and friends render line items.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::{Column, PrintOptions, Report, SortBy};
+///
+/// let options = PrintOptions::new()
+///     .sort_by(SortBy::PeakLen)
+///     .named_only(true)
+///     .summary(true)
+///     .columns(&[Column::Name, Column::PeakLen]);
+///
+/// Report::print_with(&options);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrintOptions {
+    sort_by: SortBy,
+    violations_only: bool,
+    named_only: bool,
+    name_contains: Option<CompactString>,
+    min_reallocations: usize,
+    summary: bool,
+    columns: Vec<Column>,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            sort_by: SortBy::default(),
+            violations_only: false,
+            named_only: false,
+            name_contains: None,
+            min_reallocations: 0,
+            summary: false,
+            columns: Column::ALL.to_vec(),
+        }
+    }
+}
+
+impl PrintOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sort_by(mut self, sort_by: SortBy) -> Self {
+        self.sort_by = sort_by;
+        self
+    }
+
+    /// Only show line items which exceeded their max capacity.
+    pub fn violations_only(mut self, violations_only: bool) -> Self {
+        self.violations_only = violations_only;
+        self
+    }
+
+    /// Only show line items given a name via `set_name` or `with_name`.
+    pub fn named_only(mut self, named_only: bool) -> Self {
+        self.named_only = named_only;
+        self
+    }
+
+    /// Only show line items whose name contains `pattern`.
+    pub fn name_contains(mut self, pattern: &str) -> Self {
+        self.name_contains = Some(pattern.into());
+        self
+    }
+
+    /// Only show line items which reallocated at least `min` times.
+    pub fn min_reallocations(mut self, min: usize) -> Self {
+        self.min_reallocations = min;
+        self
+    }
+
+    /// Render a single table with one row per line item, rather than a table
+    /// of events per line item.
+    pub fn summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// The columns of the summary table, in order. Defaults to `Column::ALL`.
+    pub fn columns(mut self, columns: &[Column]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Filters and sorts `line_items` as configured.
+    pub fn apply(&self, mut line_items: Vec<LineItem>) -> Vec<LineItem> {
+        line_items.retain(|line_item| {
+            (!self.violations_only || !line_item.violations().is_empty())
+                && (!self.named_only || line_item.is_named())
+                && self
+                    .name_contains
+                    .as_deref()
+                    .is_none_or(|pattern| line_item.instance_name.contains(pattern))
+                && line_item.reallocations() >= self.min_reallocations
+        });
+
+        match self.sort_by {
+            SortBy::Uid => line_items.sort_by_key(|line_item| line_item.id),
+            SortBy::Name => line_items
+                .sort_by(|a, b| a.instance_name.cmp(&b.instance_name).then(a.id.cmp(&b.id))),
+            SortBy::PeakLen => {
+                line_items.sort_by_key(|line_item| (Reverse(line_item.peak_len), line_item.id))
+            }
+            SortBy::Reallocations => line_items
+                .sort_by_key(|line_item| (Reverse(line_item.reallocations()), line_item.id)),
+        }
+
+        line_items
+    }
+
+    /// Renders `line_items` after filtering and sorting them.
+    pub fn render(&self, line_items: &[LineItem]) -> String {
+        let line_items = self.apply(line_items.to_vec());
+
+        if self.summary {
+            return format!("{}\n", self.summary_table(&line_items));
+        }
+
+        line_items
+            .iter()
+            .map(|line_item| format!("{line_item}\n"))
+            .collect()
+    }
+
+    /// Same as `render`, but writes to `writer`.
+    pub fn write_to(&self, line_items: &[LineItem], mut writer: impl Write) -> Result<(), Error> {
+        writer.write_all(self.render(line_items).as_bytes())?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    fn summary_table(&self, line_items: &[LineItem]) -> Table {
+        let mut builder = Builder::default();
+
+        builder.set_columns(self.columns.iter().map(|column| column.header()));
+
+        for line_item in line_items {
+            builder.add_record(self.columns.iter().map(|column| column.cell(line_item)));
+        }
+
+        builder.build()
+    }
+}
+
+/// Annotations are shown in place of the event's kind, so that they read
+/// inline with the rest of the history.
+fn event_cell(event: &ReportEvent) -> String {
+    match event {
+        ReportEvent::Annotation(msg) => format!("# {msg}"),
+        _ => event.kind().to_string(),
+    }
+}
+
+fn capacity_cell(event: &ReportEvent) -> String {
+    match event {
+        ReportEvent::MaxCapacitySet(cap) | ReportEvent::MaxBytesSet(cap) => cap.to_string(),
+        ReportEvent::Annotation(_) => String::new(),
+        ReportEvent::CapacityIncrease(old, new)
+        | ReportEvent::CapacityDecrease(old, new)
+        | ReportEvent::Spilled(old, new)
+        | ReportEvent::LenExceeded(old, new)
+        | ReportEvent::BytesExceeded(old, new)
+        | ReportEvent::CapacityExceeded(old, new) => {
+            format!("{old} -> {new}")
+        }
+    }
+}
+
+pub(crate) const EVENT_COLUMNS: [&str; 4] = ["event", "capacity", "len", "location"];
+
+/// The cells of every event of `line_item`, along with whether the event grew
+/// the collection past its max capacity.
+pub(crate) fn event_rows(line_item: &LineItem) -> Vec<([String; 4], bool)> {
+    let mut max_capacity = None;
+    let mut annotations = annotations_within(line_item).into_iter().peekable();
+    let mut rows = Vec::new();
+
+    for record in &line_item.events {
+        while let Some(annotation) = annotations.next_if(|a| a.timestamp < record.timestamp) {
+            let cells = [
+                format!("# {}", annotation.msg),
+                String::new(),
+                String::new(),
+                annotation.location.to_string(),
+            ];
+
+            rows.push((cells, false));
+        }
+
+        let exceeded = match record.event {
+            ReportEvent::MaxCapacitySet(cap) => {
+                max_capacity = Some(cap);
+                false
+            }
+            ReportEvent::CapacityIncrease(_, new_cap) | ReportEvent::Spilled(_, new_cap) => {
+                max_capacity.is_some_and(|max_capacity| new_cap > max_capacity)
+            }
+            ReportEvent::LenExceeded(..)
+            | ReportEvent::BytesExceeded(..)
+            | ReportEvent::CapacityExceeded(..) => true,
+            ReportEvent::CapacityDecrease(..)
+            | ReportEvent::Annotation(_)
+            | ReportEvent::MaxBytesSet(_) => false,
+        };
+        let cells = [
+            event_cell(&record.event),
+            capacity_cell(&record.event),
+            record.len.to_string(),
+            record.location.to_string(),
+        ];
+
+        rows.push((cells, exceeded));
+    }
+
+    rows
+}
+
+/// The global annotations made between the line item's first and last
+/// events. Those outside them say nothing about how it grew, and those of
+/// another run never fall inside them.
+fn annotations_within(line_item: &LineItem) -> Vec<Annotation> {
+    match (line_item.events.first(), line_item.events.last()) {
+        (Some(first), Some(last)) => Report::annotations()
+            .into_iter()
+            .filter(|a| (first.timestamp..last.timestamp).contains(&a.timestamp))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The line item's id and name, noting when it is unbudgeted.
+pub(crate) fn heading(line_item: &LineItem) -> String {
+    let unbudgeted = if line_item.is_unbudgeted() {
+        " (unbudgeted)"
+    } else {
+        ""
+    };
+
+    format!("{} - {}{unbudgeted}", line_item.id, line_item.instance_name)
+}
+
+/// One row per event, headed by the line item's id and name.
+pub(crate) fn event_table(line_item: &LineItem) -> Table {
+    let mut builder = Builder::default();
+
+    builder.set_columns(EVENT_COLUMNS);
+
+    for (cells, _) in event_rows(line_item) {
+        builder.add_record(cells);
+    }
+
+    let mut table = builder.build();
+
+    table.with(Panel::header(heading(line_item)));
+    table
+}
+
+impl Report {
+    pub fn print_with(options: &PrintOptions) {
+        print!("{}", options.render(&Report::line_items()));
+    }
+
+    /// Writes the report to `writer` rather than stdout.
+    pub fn write_with(options: &PrintOptions, writer: impl Write) -> Result<(), Error> {
+        options.write_to(&Report::line_items(), writer)
+    }
+}
+
+#[test]
+fn test_print_options() {
+    use crate::test_line_item as line_item;
+    use crate::Uid;
+
+    let line_items = [
+        line_item("small", 1, &[ReportEvent::MaxCapacitySet(4)]),
+        line_item(
+            "large",
+            20,
+            &[
+                ReportEvent::MaxCapacitySet(16),
+                ReportEvent::CapacityIncrease(16, 32),
+            ],
+        ),
+        LineItem::new(Uid::new()),
+    ];
+
+    let options = PrintOptions::new()
+        .sort_by(SortBy::PeakLen)
+        .named_only(true);
+    let names: Vec<_> = options
+        .apply(line_items.to_vec())
+        .into_iter()
+        .map(|line_item| line_item.instance_name)
+        .collect();
+
+    assert_eq!(names, ["large", "small"]);
+
+    let options = PrintOptions::new()
+        .violations_only(true)
+        .summary(true)
+        .columns(&[Column::Name, Column::MaxCapacity, Column::Reallocations]);
+    let mut rendered = std::io::Cursor::new(Vec::new());
+
+    options.write_to(&line_items, &mut rendered).unwrap();
+
+    let rendered = String::from_utf8(rendered.into_inner()).unwrap();
+
+    assert!(rendered.contains("max capacity"));
+    assert!(rendered.contains("large"));
+    assert!(!rendered.contains("small"));
+    assert!(!rendered.contains("peak len"));
+
+    let rendered = line_items[1].to_string();
+
+    assert!(rendered.contains("CapacityIncrease"));
+    assert!(rendered.contains("16 -> 32"));
+    assert!(rendered.contains(file!()));
+}
+
+#[test]
+fn test_annotation_inline() {
+    use crate::collections::HashMap;
+
+    let mut map = HashMap::with_capacity(3).with_name("annotated_map");
+
+    map.insert(0, ());
+    map.annotate("batch 42 start");
+    map.insert(1, ());
+    map.insert(2, ());
+    map.insert(3, ());
+
+    let line_item = Report::get_line_item(&map);
+    let kinds: Vec<_> = event_rows(&line_item)
+        .into_iter()
+        .map(|([event, ..], _)| event)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        ["MaxCapacitySet", "# batch 42 start", "CapacityIncrease"]
+    );
+    assert_eq!(line_item.events[1].len, 1);
+    assert_eq!(line_item.violations().len(), 1);
+    assert!(line_item.to_string().contains("# batch 42 start"));
+}
@@ -0,0 +1,140 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::{Error, LineItem, PrintOptions, Report, Uid, Violations};
+
+thread_local! {
+    static ACTIVE_SCOPES: RefCell<Vec<Arc<Mutex<Vec<Uid>>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records a newly created line item with every scope active on this thread.
+pub(crate) fn register(id: Uid) {
+    ACTIVE_SCOPES.with(|scopes| {
+        for ids in scopes.borrow().iter() {
+            ids.lock().unwrap().push(id);
+        }
+    });
+}
+
+/// Captures the collections created on the current thread while it is alive,
+/// so that they can be printed, checked or written without seeing those of
+/// other threads (such as tests running in parallel).
+///
+/// Scopes nest: a collection is captured by every scope active on its thread.
+/// Collections created on other threads are never captured.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::collections::HashMap;
+/// use max_capacity::ReportScope;
+///
+/// let scope = ReportScope::new();
+/// let mut map = HashMap::with_capacity(3).with_name("my_map");
+/// map.insert(1, 2);
+///
+/// assert_eq!(scope.line_items().len(), 1);
+/// assert!(scope.check().is_ok());
+/// ```
+pub struct ReportScope {
+    ids: Arc<Mutex<Vec<Uid>>>,
+    // Scopes are registered with the thread they were created on
+    _not_send: PhantomData<Rc<()>>,
+}
+
+impl ReportScope {
+    pub fn new() -> Self {
+        let ids = Arc::new(Mutex::new(Vec::new()));
+
+        ACTIVE_SCOPES.with(|scopes| scopes.borrow_mut().push(ids.clone()));
+
+        ReportScope {
+            ids,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// The ids of every line item created within this scope, in creation order.
+    pub fn ids(&self) -> Vec<Uid> {
+        self.ids.lock().unwrap().clone()
+    }
+
+    /// Takes a snapshot of every line item created within this scope, skipping
+    /// any which were cleared by `Report::reset`.
+    pub fn line_items(&self) -> Vec<LineItem> {
+        self.ids().into_iter().filter_map(Report::find).collect()
+    }
+
+    pub fn print(&self) {
+        self.print_with(&PrintOptions::default())
+    }
+
+    pub fn print_with(&self, options: &PrintOptions) {
+        print!("{}", options.render(&self.line_items()));
+    }
+
+    pub fn check(&self) -> Result<(), Violations> {
+        Report::check_line_items(&self.line_items())
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), Error> {
+        Report::write_line_items(path, &self.line_items())
+    }
+}
+
+impl Default for ReportScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ReportScope {
+    fn drop(&mut self) {
+        ACTIVE_SCOPES.with(|scopes| {
+            scopes
+                .borrow_mut()
+                .retain(|ids| !Arc::ptr_eq(ids, &self.ids))
+        });
+    }
+}
+
+#[test]
+fn test_scope_isolation() {
+    use crate::collections::HashMap;
+    use crate::Reportable;
+    use std::thread;
+
+    let outer = ReportScope::new();
+    let outer_map = HashMap::<u32, ()>::new();
+
+    let inner = ReportScope::new();
+    let inner_map = HashMap::<u32, ()>::new();
+    let other_thread_map = thread::spawn(|| HashMap::<u32, ()>::new().id())
+        .join()
+        .unwrap();
+
+    drop(inner);
+
+    let after_map = HashMap::<u32, ()>::new();
+
+    assert_eq!(
+        outer.ids(),
+        [outer_map.id(), inner_map.id(), after_map.id()]
+    );
+    assert!(!outer.ids().contains(&other_thread_map));
+}
+
+#[test]
+#[should_panic(expected = "no capacity violations")]
+fn test_scope_check() {
+    let scope = ReportScope::new();
+    let mut vec = crate::vec::Vec::with_capacity(1).with_name("my_vec");
+
+    vec.push(0);
+    vec.push(1);
+
+    crate::assert_no_capacity_violations!(scope);
+}
@@ -0,0 +1,391 @@
+//! Sinks receive every event as it is recorded, so that report data can be
+//! streamed out while the program runs rather than only inspected at the end.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use compact_str::CompactString;
+use log::{log, Level};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, EventRecord, LineItem, Report, Uid};
+
+static SINKS: RwLock<Vec<(SinkId, Box<dyn ReportSink>)>> = RwLock::new(Vec::new());
+static NEXT_SINK_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a registered sink so that it can be removed on its own.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SinkId(u64);
+
+/// Something which is told about every event as it is recorded.
+///
+/// `record` is called after the line item has been unlocked, so a sink may
+/// read the report. It must not add or remove sinks, or record events of its
+/// own through a tracked collection, as the list of sinks is still locked.
+pub trait ReportSink: Send + Sync {
+    /// Called after `record` has been appended to the line item. `line_item`
+    /// holds its name, type, peak length and capacity, but not its events.
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord);
+
+    /// Writes out anything the sink has buffered.
+    fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<S: ReportSink + ?Sized> ReportSink for Arc<S> {
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord) {
+        (**self).record(id, line_item, record)
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        (**self).flush()
+    }
+}
+
+pub(crate) fn dispatch(id: Uid, line_item: &LineItem, record: &EventRecord) {
+    for (_, sink) in SINKS.read().unwrap().iter() {
+        sink.record(id, line_item, record);
+    }
+}
+
+pub(crate) fn format_event(id: Uid, line_item: &LineItem, record: &EventRecord) -> String {
+    format!(
+        "{id} - {}: {:?} with len {} at {}",
+        line_item.instance_name, record.event, record.len, record.location
+    )
+}
+
+impl Report {
+    /// Registers a sink which will receive every event recorded from now on.
+    pub fn add_sink<S: ReportSink + 'static>(sink: S) -> SinkId {
+        let id = SinkId(NEXT_SINK_ID.fetch_add(1, Ordering::Relaxed));
+
+        SINKS.write().unwrap().push((id, Box::new(sink)));
+        id
+    }
+
+    /// Flushes and removes the sink registered as `id`, if it still is.
+    pub fn remove_sink(id: SinkId) -> Result<(), Error> {
+        let mut sinks = SINKS.write().unwrap();
+
+        match sinks.iter().position(|(sink_id, _)| *sink_id == id) {
+            Some(index) => {
+                let (_, sink) = sinks.remove(index);
+
+                drop(sinks);
+                sink.flush()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes and removes every registered sink.
+    pub fn clear_sinks() -> Result<(), Error> {
+        let sinks = std::mem::take(&mut *SINKS.write().unwrap());
+
+        sinks.iter().try_for_each(|(_, sink)| sink.flush())
+    }
+
+    /// Flushes every registered sink, returning the first error encountered.
+    pub fn flush_sinks() -> Result<(), Error> {
+        SINKS
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, sink)| sink.flush())
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Flushes and removes every registered sink. This should be called
+    /// before the process exits so that buffered sinks don't lose events.
+    pub fn shutdown() -> Result<(), Error> {
+        Report::clear_sinks()
+    }
+}
+
+/// Keeps every event it receives in memory until it is taken.
+///
+/// Wrap it in an `Arc` before adding it to the report to keep a handle to it.
+#[derive(Debug, Default)]
+pub struct MemorySink(Mutex<Vec<(Uid, EventRecord)>>);
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes and returns every event received so far.
+    pub fn take(&self) -> Vec<(Uid, EventRecord)> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl ReportSink for MemorySink {
+    fn record(&self, id: Uid, _line_item: &LineItem, record: &EventRecord) {
+        self.0.lock().unwrap().push((id, record.clone()));
+    }
+}
+
+/// Logs every event through the `log` crate with the `max_capacity` target.
+#[derive(Clone, Copy, Debug)]
+pub struct LogSink(pub Level);
+
+impl Default for LogSink {
+    fn default() -> Self {
+        LogSink(Level::Info)
+    }
+}
+
+impl ReportSink for LogSink {
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord) {
+        log!(target: "max_capacity", self.0, "{}", format_event(id, line_item, record));
+    }
+}
+
+/// Prints every event to stderr.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StderrSink;
+
+impl ReportSink for StderrSink {
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord) {
+        eprintln!("{}", format_event(id, line_item, record));
+    }
+}
+
+/// Appends every event to a file, one line per event.
+#[derive(Debug)]
+pub struct FileSink(Mutex<LineWriter<File>>);
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if it does not exist.
+    pub fn append(path: &Path) -> Result<Self, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(FileSink(Mutex::new(LineWriter::new(file))))
+    }
+}
+
+impl ReportSink for FileSink {
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord) {
+        // A sink has nowhere to report a failed write to, so it's dropped
+        // here and surfaced by the next flush instead
+        let _ = writeln!(
+            self.0.lock().unwrap(),
+            "{}",
+            format_event(id, line_item, record)
+        );
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        Ok(self.0.lock().unwrap().flush()?)
+    }
+}
+
+/// A single line written by a `JsonLinesSink`.
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    uid: Uid,
+    name: &'a str,
+    #[serde(rename = "type")]
+    type_name: &'a str,
+    #[serde(flatten)]
+    record: &'a EventRecord,
+}
+
+/// The owned counterpart of `JsonLine`, for reading the lines back.
+#[derive(Deserialize)]
+struct OwnedJsonLine {
+    uid: Uid,
+    name: CompactString,
+    #[serde(default, rename = "type")]
+    type_name: CompactString,
+    #[serde(flatten)]
+    record: EventRecord,
+}
+
+/// Rebuilds the line items from the lines written by a `JsonLinesSink`. A
+/// malformed final line is skipped, as it was likely cut short by the process
+/// being killed mid-write.
+pub(crate) fn read_json_lines(contents: &str) -> Result<Vec<LineItem>, Error> {
+    let mut line_items = BTreeMap::new();
+    let mut lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+
+    while let Some(line) = lines.next() {
+        let OwnedJsonLine {
+            uid,
+            name,
+            type_name,
+            record,
+        } = match serde_json::from_str(line) {
+            Ok(line) => line,
+            Err(_) if lines.peek().is_none() => break,
+            Err(err) => return Err(Error::MalformedReport(err)),
+        };
+        let line_item = line_items.entry(uid).or_insert_with(|| LineItem::new(uid));
+
+        line_item.instance_name = name;
+        line_item.type_name = type_name;
+        line_item.push(record);
+    }
+
+    Ok(line_items.into_values().collect())
+}
+
+/// Appends every event to a file as a line of JSON, for example:
+///
+/// ```text
+/// {"uid":3,"name":"my_map","type":"HashMap","event":{"CapacityIncrease":[3,7]},"len":4,"timestamp_us":1666000000000000,"location":"src/main.rs:12:9"}
+/// ```
+///
+/// Each line is flushed as soon as it is written, so the file holds every
+/// event up to the last even if the process is killed.
+#[derive(Debug)]
+pub struct JsonLinesSink(Mutex<LineWriter<File>>);
+
+impl JsonLinesSink {
+    /// Opens `path` for appending, creating it if it does not exist.
+    pub fn append(path: &Path) -> Result<Self, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(JsonLinesSink(Mutex::new(LineWriter::new(file))))
+    }
+}
+
+impl ReportSink for JsonLinesSink {
+    fn record(&self, id: Uid, line_item: &LineItem, record: &EventRecord) {
+        let line = JsonLine {
+            uid: id,
+            name: &line_item.instance_name,
+            type_name: &line_item.type_name,
+            record,
+        };
+        let mut writer = self.0.lock().unwrap();
+
+        // Same as the FileSink, failures will resurface on the next flush
+        if serde_json::to_writer(&mut *writer, &line).is_ok() {
+            let _ = writer.write_all(b"\n");
+        }
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        Ok(self.0.lock().unwrap().flush()?)
+    }
+}
+
+#[test]
+fn test_memory_sink() {
+    use crate::collections::HashMap;
+    use crate::Reportable;
+
+    let sink = Arc::new(MemorySink::new());
+
+    let sink_id = Report::add_sink(sink.clone());
+
+    let mut map = HashMap::<u32, ()>::with_capacity(3);
+
+    map.insert(0, ());
+    map.insert(1, ());
+    map.insert(2, ());
+    map.insert(3, ());
+    Report::remove_sink(sink_id).unwrap();
+    map.shrink_to_fit();
+
+    let events: Vec<_> = sink
+        .take()
+        .into_iter()
+        .filter(|(id, _)| *id == map.id())
+        .map(|(_, record)| record.event)
+        .collect();
+
+    assert_eq!(
+        events,
+        [
+            crate::ReportEvent::MaxCapacitySet(3),
+            crate::ReportEvent::CapacityIncrease(3, 7)
+        ]
+    );
+}
+
+#[test]
+fn test_sink_reads_report() {
+    use crate::vec::Vec;
+    use crate::Reportable;
+
+    /// Looks up the line item of every event it's given, which deadlocks if
+    /// the report is still locked.
+    struct ReadingSink(Mutex<std::vec::Vec<(Uid, usize)>>);
+
+    impl ReportSink for ReadingSink {
+        fn record(&self, id: Uid, _line_item: &LineItem, _record: &EventRecord) {
+            if let Some(line_item) = Report::find(id) {
+                self.0.lock().unwrap().push((id, line_item.events.len()));
+            }
+        }
+    }
+
+    let sink = Arc::new(ReadingSink(Mutex::new(std::vec::Vec::new())));
+    let sink_id = Report::add_sink(sink.clone());
+    let mut vec = Vec::with_capacity(1).with_name("test_sink_reads_report");
+
+    vec.push(0);
+    vec.push(1);
+    Report::remove_sink(sink_id).unwrap();
+
+    let seen: std::vec::Vec<_> = sink
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(id, _)| *id == vec.id())
+        .map(|&(_, events)| events)
+        .collect();
+
+    assert_eq!(seen, [1, 2]);
+}
+
+#[test]
+fn test_json_lines_sink() {
+    use std::fs;
+
+    let path = std::env::temp_dir().join(format!("max_capacity_{}.jsonl", std::process::id()));
+    let sink = JsonLinesSink::append(&path).unwrap();
+    let id = Report::new_line_item("Vec", 0);
+    let line_item = crate::LineItem {
+        instance_name: "my_vec".into(),
+        ..crate::LineItem::new(id)
+    };
+    let record = EventRecord::new(crate::ReportEvent::CapacityIncrease(4, 8), 5);
+
+    sink.record(id, &line_item, &record);
+    sink.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    let json: serde_json::Value = serde_json::from_str(contents.trim_end()).unwrap();
+
+    assert_eq!(json["uid"], serde_json::json!(id));
+    assert_eq!(json["name"], "my_vec");
+    assert_eq!(json["event"]["CapacityIncrease"], serde_json::json!([4, 8]));
+    assert_eq!(json["len"], 5);
+    assert!(json["location"]
+        .as_str()
+        .unwrap()
+        .starts_with("src/sink.rs:"));
+    assert!(json["timestamp_us"].as_u64().unwrap() > 0);
+
+    let line_items = read_json_lines(&format!("{contents}{{\"uid\":")).unwrap();
+
+    assert_eq!(line_items.len(), 1);
+    assert_eq!(line_items[0].instance_name, "my_vec");
+    assert_eq!(line_items[0].events[0].event, record.event);
+    assert_eq!(line_items[0].events[0].location, record.location);
+    assert_eq!(line_items[0].peak_len, 5);
+}
@@ -0,0 +1,457 @@
+//! A capacity tracking wrapper for [`smallvec::SmallVec`], enabled by the
+//! `smallvec` feature.
+
+use smallvec::{Array, Drain, IntoIter, SmallVec as BaseSmallVec};
+
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
+
+use std::any::type_name;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::ops::{Deref, DerefMut, RangeBounds};
+use std::slice::{Iter, IterMut};
+
+/// A `smallvec::SmallVec` which reports its capacity changes, along with its
+/// cached peak length.
+///
+/// Moving from inline storage onto the heap is reported as a `Spilled` event
+/// rather than a plain capacity increase, as avoiding it is usually the point
+/// of a `SmallVec`.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::smallvec::SmallVec;
+/// use max_capacity::{Report, ReportEvent};
+///
+/// let mut vec = SmallVec::<[u8; 2]>::new().with_name("my_small_vec");
+///
+/// vec.extend([1, 2, 3]);
+///
+/// assert!(vec.spilled());
+/// assert!(matches!(
+///     Report::get_line_item(&vec).events[0].event,
+///     ReportEvent::Spilled(2, _)
+/// ));
+/// ```
+pub struct SmallVec<A: Array>(BaseSmallVec<A>, Tracker);
+
+// SmallVec
+impl<A: Array> SmallVec<A> {
+    /// Constructs an empty vector, which will not allocate until it outgrows
+    /// its inline capacity.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs an empty vector with enough capacity for `n` elements,
+    /// spilling onto the heap straight away if `n` is over the inline
+    /// capacity.
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(n: usize) -> Self {
+        let vec = BaseSmallVec::with_capacity(n);
+        let tracker =
+            Tracker::new("SmallVec", vec.capacity()).with_inline_capacity(vec.inline_size());
+        tracker.record(ReportEvent::MaxCapacitySet(n), 0);
+
+        Self(vec, tracker)
+    }
+
+    /// Constructs a vector from a `Vec`, storing the elements inline if they
+    /// fit.
+    #[track_caller]
+    pub fn from_vec(vec: Vec<A::Item>) -> Self {
+        BaseSmallVec::from_vec(vec).into()
+    }
+
+    /// The maximum number of elements this vector can hold inline.
+    #[inline]
+    pub fn inline_size(&self) -> usize {
+        self.0.inline_size()
+    }
+
+    /// The number of elements stored in the vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the vector is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of items the vector can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns `true` if the data has spilled onto the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        self.0.spilled()
+    }
+
+    /// Extracts a slice containing the entire vector.
+    #[inline]
+    pub fn as_slice(&self) -> &[A::Item] {
+        self.0.as_slice()
+    }
+
+    /// Extracts a mutable slice of the entire vector.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [A::Item] {
+        self.0.as_mut_slice()
+    }
+
+    /// Appends an item to the back of the vector.
+    #[track_caller]
+    pub fn push(&mut self, value: A::Item) {
+        self.0.push(value);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Removes an item from the end of the vector and returns it, or `None`
+    /// if empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<A::Item> {
+        let ret = self.0.pop();
+        self.observe_len();
+        ret
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it
+    /// to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, element: A::Item) {
+        self.0.insert(index, element);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Removes and returns the element at position `index`, shifting all
+    /// elements after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> A::Item {
+        let ret = self.0.remove(index);
+        self.observe_len();
+        ret
+    }
+
+    /// Removes the element at position `index`, replacing it with the last
+    /// element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) -> A::Item {
+        let ret = self.0.swap_remove(index);
+        self.observe_len();
+        ret
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the
+    /// rest. The capacity is left untouched.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+        self.observe_len();
+    }
+
+    /// Removes all elements from the vector. The capacity is left untouched.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    /// Creates a draining iterator that removes the specified range in the
+    /// vector and yields the removed items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, A>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.1.observe_drain(self.0.len(), &range);
+        self.0.drain(range)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut A::Item) -> bool,
+    {
+        self.0.retain(f);
+        self.observe_len();
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    /// Reserves the minimum capacity for `additional` more elements.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+        self.observe_capacity();
+    }
+
+    /// Shrinks the capacity of the vector as much as possible, moving the
+    /// elements back inline if they fit.
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    /// Converts the vector into a `Vec`, without reallocating if it has
+    /// already spilled onto the heap.
+    #[inline]
+    pub fn into_vec(self) -> Vec<A::Item> {
+        self.0.into_vec()
+    }
+
+    /// Returns an iterator over the vector.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator that allows modifying each value.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, A::Item> {
+        self.0.iter_mut()
+    }
+}
+
+// SmallVec
+impl<A: Array> SmallVec<A>
+where
+    A::Item: Copy,
+{
+    /// Copies the elements from a slice onto the end of the vector.
+    #[track_caller]
+    pub fn extend_from_slice(&mut self, slice: &[A::Item]) {
+        self.0.extend_from_slice(slice);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+// Extra
+impl<A: Array> SmallVec<A> {
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+}
+
+impl<A: Array> Deref for SmallVec<A> {
+    type Target = [A::Item];
+
+    #[inline]
+    fn deref(&self) -> &[A::Item] {
+        &self.0
+    }
+}
+
+impl<A: Array> DerefMut for SmallVec<A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [A::Item] {
+        &mut self.0
+    }
+}
+
+impl<A: Array> IntoIterator for SmallVec<A> {
+    type Item = A::Item;
+    type IntoIter = IntoIter<A>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<A> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, A: Array> IntoIterator for &'a SmallVec<A> {
+    type Item = &'a A::Item;
+    type IntoIter = Iter<'a, A::Item>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, A::Item> {
+        self.0.iter()
+    }
+}
+
+impl<'a, A: Array> IntoIterator for &'a mut SmallVec<A> {
+    type Item = &'a mut A::Item;
+    type IntoIter = IterMut<'a, A::Item>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, A::Item> {
+        self.0.iter_mut()
+    }
+}
+
+impl<A: Array> Clone for SmallVec<A>
+where
+    A::Item: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+}
+
+impl<A: Array> Display for SmallVec<A> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(fmt, "{name}: SmallVec<{}>", type_name::<A>())
+    }
+}
+
+// SmallVec
+impl<A: Array> Debug for SmallVec<A>
+where
+    A::Item: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<A: Array> Default for SmallVec<A> {
+    fn default() -> Self {
+        let vec = BaseSmallVec::new();
+        let tracker =
+            Tracker::new("SmallVec", vec.capacity()).with_inline_capacity(vec.inline_size());
+
+        Self(vec, tracker)
+    }
+}
+
+impl<A: Array> PartialEq for SmallVec<A>
+where
+    A::Item: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<A: Array> Eq for SmallVec<A> where A::Item: Eq {}
+
+impl<A: Array> From<BaseSmallVec<A>> for SmallVec<A> {
+    #[track_caller]
+    fn from(vec: BaseSmallVec<A>) -> Self {
+        let tracker =
+            Tracker::new("SmallVec", vec.capacity()).with_inline_capacity(vec.inline_size());
+        let mut vec = Self(vec, tracker);
+        vec.observe_len();
+        vec
+    }
+}
+
+impl<A: Array> From<SmallVec<A>> for BaseSmallVec<A> {
+    fn from(vec: SmallVec<A>) -> Self {
+        vec.0
+    }
+}
+
+impl<A: Array> From<Vec<A::Item>> for SmallVec<A> {
+    #[track_caller]
+    fn from(vec: Vec<A::Item>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl<A: Array> FromIterator<A::Item> for SmallVec<A> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = A::Item>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<A: Array> Extend<A::Item> for SmallVec<A> {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = A::Item>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<A: Array> Reportable for SmallVec<A> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_spill_report() {
+    let mut vec = SmallVec::<[u32; 2]>::new().with_name("my_small_vec");
+
+    vec.push(0);
+    vec.push(1);
+    vec.push(2);
+    vec.extend(3..8);
+    vec.truncate(2);
+    vec.shrink_to_fit();
+
+    let line_item = Report::get_line_item(&vec);
+
+    assert_eq!(line_item.type_name, "SmallVec");
+    assert_eq!(line_item.events[0], ReportEvent::Spilled(2, 4));
+    assert_eq!(line_item.events[1], ReportEvent::CapacityIncrease(4, 8));
+    assert_eq!(line_item.events[2], ReportEvent::CapacityDecrease(8, 2));
+    assert_eq!(line_item.events.len(), 3);
+    assert_eq!(line_item.reallocations(), 2);
+    assert!(!vec.spilled());
+}
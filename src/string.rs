@@ -0,0 +1,366 @@
+use std::borrow::Borrow;
+use std::fmt::{self, Debug, Display, Formatter, Result as FmtResult};
+use std::ops::{Deref, DerefMut};
+use std::string::String as StdString;
+
+use crate::{Error, ReportEvent, Reportable, Tracker, Uid};
+
+/// A `std::string::String` which reports its capacity changes, in bytes,
+/// along with its cached peak length.
+///
+/// Unlike the other wrappers, `Display` and `Debug` format the contents of the
+/// string just as `std::string::String` does, so that it can stand in for one
+/// when formatting.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+/// use max_capacity::string::String;
+///
+/// let mut buf = String::with_capacity(16).with_name("my_buf");
+///
+/// write!(buf, "{}-{}", "hello", 42).unwrap();
+/// assert_eq!(&*buf, "hello-42");
+/// ```
+pub struct String(StdString, Tracker);
+
+impl Default for String {
+    fn default() -> Self {
+        Self(StdString::new(), Tracker::new("String", 0))
+    }
+}
+
+// Std String methods
+impl String {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> Self {
+        let string = StdString::with_capacity(cap);
+        let tracker = Tracker::new("String", string.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(cap), 0);
+
+        Self(string, tracker)
+    }
+
+    /// The length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The capacity of the string in bytes.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[track_caller]
+    pub fn push(&mut self, ch: char) {
+        self.0.push(ch);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    #[track_caller]
+    pub fn push_str(&mut self, string: &str) {
+        self.0.push_str(string);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Inserts a character at the byte position `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a char boundary.
+    #[track_caller]
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.0.insert(idx, ch);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    /// Inserts a string slice at the byte position `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length, or if it does not
+    /// lie on a char boundary.
+    #[track_caller]
+    pub fn insert_str(&mut self, idx: usize, string: &str) {
+        self.0.insert_str(idx, string);
+        self.observe_len();
+        self.observe_capacity();
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let ret = self.0.pop();
+        self.observe_len();
+        ret
+    }
+
+    /// Shortens the string to `new_len` bytes. The capacity is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` does not lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        self.0.truncate(new_len);
+        self.observe_len();
+    }
+
+    /// Empties the string. The capacity is left untouched.
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.observe_len();
+    }
+
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.observe_capacity();
+    }
+
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+        self.observe_capacity();
+    }
+
+    /// Tries to reserve capacity for at least `additional` more bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the string would need to hold more bytes than its max capacity,
+    /// [`Error::HardCapRejected`] is returned and nothing is reserved.
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    #[track_caller]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        self.1.check_hard_cap(self.0.len(), additional)?;
+        self.0.try_reserve(additional)?;
+        self.observe_capacity();
+
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+        self.observe_capacity();
+    }
+
+    #[track_caller]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity);
+        self.observe_capacity();
+    }
+}
+
+// Extra String methods
+impl String {
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+
+    #[inline]
+    #[track_caller]
+    fn observe_capacity(&mut self) {
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+    }
+
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+}
+
+impl fmt::Write for String {
+    #[track_caller]
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[track_caller]
+    fn write_char(&mut self, c: char) -> FmtResult {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl Deref for String {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl DerefMut for String {
+    fn deref_mut(&mut self) -> &mut str {
+        &mut self.0
+    }
+}
+
+impl AsRef<str> for String {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for String {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Clone for String {
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+}
+
+impl Display for String {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for String {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq for String {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for String {}
+
+impl PartialEq<str> for String {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for String {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl From<StdString> for String {
+    fn from(string: StdString) -> Self {
+        let tracker = Tracker::new("String", string.capacity());
+        let mut string = Self(string, tracker);
+        string.observe_len();
+        string
+    }
+}
+
+impl From<&str> for String {
+    fn from(string: &str) -> Self {
+        StdString::from(string).into()
+    }
+}
+
+impl From<String> for StdString {
+    fn from(string: String) -> Self {
+        string.0
+    }
+}
+
+impl FromIterator<char> for String {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut string = String::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl Extend<char> for String {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl<'a> Extend<&'a str> for String {
+    #[track_caller]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.observe_len();
+        self.observe_capacity();
+    }
+}
+
+impl Reportable for String {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+#[test]
+fn test_string_report() {
+    use crate::Report;
+    use std::fmt::Write;
+
+    let mut string = String::with_capacity(4).with_name("my_string");
+
+    string.push_str("abcd");
+    write!(string, "{}", 12345).unwrap();
+    string.insert_str(0, "> ");
+    string.clear();
+
+    let grown_cap = string.capacity();
+
+    string.shrink_to(0);
+
+    let line_item = Report::get_line_item(&string);
+
+    assert_eq!(line_item.type_name, "String");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(4));
+    assert!(matches!(
+        line_item.events[1].event,
+        ReportEvent::CapacityIncrease(4, _)
+    ));
+    assert_eq!(
+        *line_item.events.last().unwrap(),
+        ReportEvent::CapacityDecrease(grown_cap, 0)
+    );
+    assert_eq!(line_item.peak_len, 11);
+    assert_eq!(line_item.violations().len(), 1);
+}
@@ -0,0 +1,158 @@
+//! Exports the report in the Chrome Trace Event Format, which can be loaded
+//! into `chrome://tracing` or Perfetto to see how capacity and length change
+//! over time. Every line item becomes a counter track, with a sample per
+//! event:
+//!
+//! ```text
+//! {"name":"my_map (0x3)","ph":"C","ts":1666000000000000,"pid":42,"args":{"capacity":7,"len":4,"max_capacity":3}}
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::{Error, LineItem, Report, ReportEvent};
+
+#[derive(Serialize)]
+struct Trace {
+    #[serde(rename = "traceEvents")]
+    trace_events: Vec<CounterEvent>,
+    #[serde(rename = "displayTimeUnit")]
+    display_time_unit: &'static str,
+}
+
+#[derive(Serialize)]
+struct CounterEvent {
+    name: String,
+    ph: &'static str,
+    #[serde(serialize_with = "crate::micros::serialize")]
+    ts: SystemTime,
+    pid: u32,
+    args: Counters,
+}
+
+/// Each field is drawn as its own series within the track.
+#[derive(Serialize)]
+struct Counters {
+    capacity: usize,
+    len: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_capacity: Option<usize>,
+}
+
+fn counter_events(line_item: &LineItem) -> impl Iterator<Item = CounterEvent> + '_ {
+    let name = format!("{} ({})", line_item.instance_name, line_item.id);
+    let pid = process::id();
+
+    // The capacity before the first change, so the track starts where the
+    // collection did
+    let mut capacity = line_item
+        .events
+        .iter()
+        .find_map(|record| match record.event {
+            ReportEvent::CapacityIncrease(old_cap, _)
+            | ReportEvent::CapacityDecrease(old_cap, _)
+            | ReportEvent::Spilled(old_cap, _) => Some(old_cap),
+            ReportEvent::MaxCapacitySet(_)
+            | ReportEvent::Annotation(_)
+            | ReportEvent::LenExceeded(..)
+            | ReportEvent::MaxBytesSet(_)
+            | ReportEvent::BytesExceeded(..)
+            | ReportEvent::CapacityExceeded(..) => None,
+        })
+        .unwrap_or(line_item.capacity);
+    let mut max_capacity = None;
+
+    line_item.events.iter().map(move |record| {
+        match record.event {
+            ReportEvent::MaxCapacitySet(cap) => max_capacity = Some(cap),
+            ReportEvent::CapacityIncrease(_, new_cap)
+            | ReportEvent::CapacityDecrease(_, new_cap)
+            | ReportEvent::Spilled(_, new_cap) => capacity = new_cap,
+            ReportEvent::Annotation(_)
+            | ReportEvent::LenExceeded(..)
+            | ReportEvent::MaxBytesSet(_)
+            | ReportEvent::BytesExceeded(..)
+            | ReportEvent::CapacityExceeded(..) => (),
+        }
+
+        CounterEvent {
+            name: name.clone(),
+            ph: "C",
+            ts: record.timestamp,
+            pid,
+            args: Counters {
+                capacity,
+                len: record.len,
+                max_capacity,
+            },
+        }
+    })
+}
+
+fn render(line_items: &[LineItem]) -> String {
+    let mut trace_events: Vec<_> = line_items.iter().flat_map(counter_events).collect();
+
+    trace_events.sort_by_key(|event| event.ts);
+
+    let trace = Trace {
+        trace_events,
+        display_time_unit: "ms",
+    };
+
+    serde_json::to_string(&trace).expect("traces always serialize")
+}
+
+impl Report {
+    /// Writes every line item as a counter track in the Chrome Trace Event
+    /// Format. See the [`trace`](crate::trace) module for details.
+    pub fn export_chrome_trace(path: &Path) -> Result<(), Error> {
+        Ok(fs::write(path, Report::render_chrome_trace())?)
+    }
+
+    /// Same as `export_chrome_trace`, but returns the trace as a string.
+    pub fn render_chrome_trace() -> String {
+        render(&Report::line_items())
+    }
+}
+
+#[test]
+fn test_render() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::{EventRecord, Uid};
+
+    let record = |event, len, micros| EventRecord {
+        timestamp: UNIX_EPOCH + Duration::from_micros(micros),
+        ..EventRecord::new(event, len)
+    };
+    let id = Uid::new();
+    let line_item = LineItem {
+        instance_name: "my_map".into(),
+        events: vec![
+            record(ReportEvent::MaxCapacitySet(4), 0, 10),
+            record(ReportEvent::CapacityIncrease(4, 8), 5, 20),
+        ],
+        capacity: 8,
+        ..LineItem::new(id)
+    };
+    let trace: serde_json::Value = serde_json::from_str(&render(&[line_item])).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["name"], format!("my_map ({id})"));
+    assert_eq!(events[0]["ph"], "C");
+    assert_eq!(events[0]["ts"], 10);
+    assert_eq!(
+        events[0]["args"],
+        serde_json::json!({"capacity": 4, "len": 0, "max_capacity": 4})
+    );
+    assert_eq!(events[1]["ts"], 20);
+    assert_eq!(
+        events[1]["args"],
+        serde_json::json!({"capacity": 8, "len": 5, "max_capacity": 4})
+    );
+}
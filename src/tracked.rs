@@ -0,0 +1,276 @@
+//! Capacity tracking for any container which implements [`Capacity`], for
+//! when there's no dedicated wrapper such as [`vec::Vec`](crate::vec::Vec).
+
+use std::any::type_name;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::ops::{Deref, DerefMut};
+
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
+
+/// A container with a length and a capacity, both counted in elements.
+pub trait Capacity {
+    /// The number of elements in the container.
+    fn len(&self) -> usize;
+
+    /// The number of elements the container can hold without reallocating.
+    fn capacity(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Wraps a container `C`, reporting the capacity changes made inside of
+/// [`track`](Tracked::track) along with its peak length.
+///
+/// The last capacity seen is cached, so changes made through `DerefMut` are
+/// recorded by the next call to `track`, against its own location.
+///
+/// # Examples
+///
+/// ```
+/// use max_capacity::{Capacity, Report, ReportEvent, Tracked};
+///
+/// #[derive(Default)]
+/// struct Arena {
+///     slots: Vec<u64>,
+/// }
+///
+/// impl Capacity for Arena {
+///     fn len(&self) -> usize {
+///         self.slots.len()
+///     }
+///
+///     fn capacity(&self) -> usize {
+///         self.slots.capacity()
+///     }
+/// }
+///
+/// let mut arena = Tracked::new(Arena::default()).with_name("my_arena");
+///
+/// arena.track(|arena| arena.slots.push(0));
+///
+/// let line_item = Report::get_line_item(&arena);
+///
+/// assert_eq!(line_item.type_name, "Arena");
+/// assert!(matches!(
+///     line_item.events[0].event,
+///     ReportEvent::CapacityIncrease(0, _)
+/// ));
+/// ```
+pub struct Tracked<C>(C, Tracker);
+
+impl<C: Capacity> Tracked<C> {
+    /// Starts tracking `inner` from its current capacity.
+    pub fn new(inner: C) -> Self {
+        let tracker = Tracker::new(short_type_name::<C>(), inner.capacity());
+        let mut tracked = Self(inner, tracker);
+        tracked.observe_len();
+        tracked
+    }
+
+    /// Runs `f` on the container, recording any change it makes to the
+    /// capacity.
+    #[track_caller]
+    pub fn track<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut C) -> R,
+    {
+        let ret = f(&mut self.0);
+        self.observe_len();
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
+        ret
+    }
+
+    /// Sets a capacity that the container should never grow past.
+    #[track_caller]
+    pub fn set_max_capacity(&mut self, max_capacity: usize) {
+        self.1
+            .record(ReportEvent::MaxCapacitySet(max_capacity), self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_max_capacity(mut self, max_capacity: usize) -> Self {
+        self.set_max_capacity(max_capacity);
+        self
+    }
+
+    #[track_caller]
+    pub fn set_name(&mut self, name: &str) {
+        self.1.set_name(name, self.0.len());
+    }
+
+    #[track_caller]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
+
+    /// Stops tracking, returning the container.
+    pub fn into_inner(self) -> C {
+        self.0
+    }
+
+    #[inline]
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
+    }
+}
+
+/// The name of `C` without its path or generics, such as `Vec` for
+/// `alloc::vec::Vec<u8>`.
+fn short_type_name<C>() -> &'static str {
+    let name = type_name::<C>();
+    let name = name.split('<').next().unwrap_or(name);
+
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl<C> Deref for Tracked<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.0
+    }
+}
+
+impl<C> DerefMut for Tracked<C> {
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.0
+    }
+}
+
+impl<C: Capacity + Clone> Clone for Tracked<C> {
+    fn clone(&self) -> Self {
+        let inner = self.0.clone();
+        let tracker = self.1.cloned(inner.capacity());
+
+        Self(inner, tracker)
+    }
+}
+
+impl<C: Capacity + Default> Default for Tracked<C> {
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+impl<C: Capacity> From<C> for Tracked<C> {
+    fn from(inner: C) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<C> Display for Tracked<C> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        let name = &Report::get(self.1.id()).instance_name;
+        write!(fmt, "{name}: {}", type_name::<C>())
+    }
+}
+
+impl<C: Debug> Debug for Tracked<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<C> Reportable for Tracked<C> {
+    fn id(&self) -> Uid {
+        self.1.id()
+    }
+}
+
+macro_rules! impl_capacity {
+    ($($ty:ty => [$($generics:tt)*]),* $(,)?) => {
+        $(
+            impl<$($generics)*> Capacity for $ty {
+                fn len(&self) -> usize {
+                    self.len()
+                }
+
+                fn capacity(&self) -> usize {
+                    self.capacity()
+                }
+            }
+        )*
+    };
+}
+
+impl_capacity! {
+    Vec<T> => [T],
+    VecDeque<T> => [T],
+    BinaryHeap<T> => [T],
+    HashMap<K, V, S> => [K, V, S],
+    HashSet<T, S> => [T, S],
+    String => [],
+}
+
+#[cfg(feature = "hashbrown")]
+impl_capacity! {
+    hashbrown::HashMap<K, V, S> => [K, V, S],
+    hashbrown::HashSet<T, S> => [T, S],
+}
+
+#[cfg(feature = "indexmap")]
+impl_capacity! {
+    indexmap::IndexMap<K, V, S> => [K, V, S],
+    indexmap::IndexSet<T, S> => [T, S],
+}
+
+#[cfg(feature = "smallvec")]
+impl_capacity! {
+    smallvec::SmallVec<A> => [A: smallvec::Array],
+}
+
+#[test]
+fn test_track() {
+    let mut vec = Tracked::new(Vec::<u32>::new())
+        .with_max_capacity(2)
+        .with_name("my_tracked_vec");
+
+    vec.track(|vec| vec.extend([0, 1, 2]));
+    vec.clear();
+    vec.track(|vec| vec.shrink_to_fit());
+
+    let line_item = Report::get_line_item(&vec);
+
+    assert_eq!(line_item.type_name, "Vec");
+    assert_eq!(line_item.events[0], ReportEvent::MaxCapacitySet(2));
+    assert!(matches!(
+        line_item.events[1].event,
+        ReportEvent::CapacityIncrease(0, _)
+    ));
+    assert!(matches!(
+        line_item.events[2].event,
+        ReportEvent::CapacityDecrease(_, 0)
+    ));
+    assert_eq!(line_item.peak_len, 3);
+    assert_eq!(line_item.violations().len(), 1);
+}
+
+#[test]
+fn test_track_deref_mut() {
+    use crate::EventRecord;
+
+    let mut vec = Tracked::new(Vec::<u32>::new()).with_name("my_deref_vec");
+
+    vec.reserve(8);
+    vec.track(|vec| vec.push(0));
+
+    let line_item = Report::get_line_item(&vec);
+
+    assert!(matches!(
+        line_item.events[..],
+        [EventRecord {
+            event: ReportEvent::CapacityIncrease(0, 8..),
+            len: 1,
+            ..
+        }]
+    ));
+}
@@ -0,0 +1,282 @@
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+
+use crate::{Error, Report, ReportEvent, ReportHandle, Uid};
+
+/// What a tracker publishes about its collection without locking the report,
+/// to be folded into the line item whenever it's read, or to restore it after
+/// a reset. Clones share a line item, but each publishes its own so that none
+/// has to read another's.
+#[derive(Debug)]
+struct Published {
+    type_name: &'static str,
+    len: AtomicUsize,
+    /// Compared against directly rather than cached by the tracker, so that
+    /// a reset can lower it.
+    peak_len: AtomicUsize,
+    capacity: AtomicUsize,
+}
+
+/// What the live trackers of a collection have published, with the length
+/// of its clones added up.
+pub(crate) struct Live {
+    pub(crate) type_name: &'static str,
+    pub(crate) len: usize,
+    pub(crate) peak_len: usize,
+    pub(crate) capacity: usize,
+}
+
+/// What the trackers of every live, tracked collection have published.
+static PUBLISHED: OnceCell<DashMap<Uid, Vec<Arc<Published>>>> = OnceCell::new();
+
+/// Starts publishing for a tracker of `id`.
+fn register(id: Uid, published: Published) -> Arc<Published> {
+    let published = Arc::new(published);
+
+    if Report::config().is_tracked(id) {
+        PUBLISHED
+            .get_or_init(DashMap::new)
+            .entry(id)
+            .or_default()
+            .push(published.clone());
+    }
+
+    published
+}
+
+/// Stops publishing for a tracker of `id`, returning whether it was the last
+/// one.
+fn unregister(id: Uid, published: &Arc<Published>) -> bool {
+    let Some(all_published) = PUBLISHED.get() else {
+        return false;
+    };
+
+    let Some(mut trackers) = all_published.get_mut(&id) else {
+        return false;
+    };
+
+    trackers.retain(|other| !Arc::ptr_eq(other, published));
+
+    let last = trackers.is_empty();
+
+    drop(trackers);
+
+    if last {
+        all_published.remove_if(&id, |_, trackers| trackers.is_empty());
+    }
+
+    last
+}
+
+/// What the live trackers of `id` have published, if it has any.
+pub(crate) fn published(id: Uid) -> Option<Live> {
+    let trackers = PUBLISHED.get()?.get(&id)?;
+    let mut live = Live {
+        type_name: trackers.first()?.type_name,
+        len: 0,
+        peak_len: 0,
+        capacity: 0,
+    };
+
+    for published in trackers.iter() {
+        live.len += published.len.load(Ordering::Relaxed);
+        live.peak_len = live
+            .peak_len
+            .max(published.peak_len.load(Ordering::Relaxed));
+        live.capacity = live
+            .capacity
+            .max(published.capacity.load(Ordering::Relaxed));
+    }
+
+    Some(live)
+}
+
+/// Starts the peak length of every live collection over from its current
+/// length, returning their ids so that their line items can be restored.
+pub(crate) fn reset_published() -> Vec<Uid> {
+    let Some(all_published) = PUBLISHED.get() else {
+        return Vec::new();
+    };
+
+    all_published
+        .iter()
+        .map(|trackers| {
+            for published in trackers.iter() {
+                let len = published.len.load(Ordering::Relaxed);
+
+                published.peak_len.store(len, Ordering::Relaxed);
+            }
+
+            *trackers.key()
+        })
+        .collect()
+}
+
+/// The state every collection wrapper keeps alongside its collection: the
+/// handle to its line item, and the peak length and capacity it last reported
+/// so that the report is only touched when either of them changes. Dropping
+/// it marks the line item dropped, once every clone is gone too.
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    handle: ReportHandle,
+    /// Only ever written by this tracker, other than by a reset, so updating
+    /// it needs no atomic read-modify-write.
+    published: Arc<Published>,
+    capacity: usize,
+    /// Growing past this capacity is reported as a spill, for collections
+    /// which store elements inline.
+    inline_capacity: Option<usize>,
+}
+
+impl Tracker {
+    /// Adds a line item for a collection of `type_name` with an initial
+    /// `capacity`.
+    pub(crate) fn new(type_name: &'static str, capacity: usize) -> Self {
+        let handle = ReportHandle::new(type_name, capacity);
+
+        Tracker {
+            handle,
+            published: register(
+                handle.id(),
+                Published {
+                    type_name,
+                    len: AtomicUsize::new(0),
+                    peak_len: AtomicUsize::new(0),
+                    capacity: AtomicUsize::new(capacity),
+                },
+            ),
+            capacity,
+            inline_capacity: None,
+        }
+    }
+
+    #[cfg(feature = "smallvec")]
+    pub(crate) fn with_inline_capacity(mut self, inline_capacity: usize) -> Self {
+        self.inline_capacity = Some(inline_capacity);
+        self
+    }
+
+    /// A tracker for a clone of the collection, which shares its line item.
+    pub(crate) fn cloned(&self, capacity: usize) -> Self {
+        Tracker {
+            handle: self.handle,
+            published: register(
+                self.id(),
+                Published {
+                    type_name: self.published.type_name,
+                    len: AtomicUsize::new(self.published.len.load(Ordering::Relaxed)),
+                    peak_len: AtomicUsize::new(self.peak_len()),
+                    capacity: AtomicUsize::new(capacity),
+                },
+            ),
+            capacity,
+            inline_capacity: self.inline_capacity,
+        }
+    }
+
+    pub(crate) fn id(&self) -> Uid {
+        self.handle.id()
+    }
+
+    fn peak_len(&self) -> usize {
+        self.published.peak_len.load(Ordering::Relaxed)
+    }
+
+    #[track_caller]
+    pub(crate) fn record(&self, event: ReportEvent, len: usize) {
+        self.handle.record(event, len);
+    }
+
+    #[track_caller]
+    pub(crate) fn set_name(&self, name: &str, len: usize) {
+        self.handle.set_name(name, len);
+    }
+
+    #[track_caller]
+    pub(crate) fn annotate(&self, msg: &str, len: usize) {
+        self.handle.annotate(msg, len);
+    }
+
+    /// Publishes `len` as the current length, and as the peak if it's a new
+    /// one, returning whether it was. Called after anything which may change
+    /// the length.
+    #[inline]
+    pub(crate) fn observe_len(&mut self, len: usize) -> bool {
+        self.published.len.store(len, Ordering::Relaxed);
+
+        if len <= self.peak_len() {
+            return false;
+        }
+
+        self.published.peak_len.store(len, Ordering::Relaxed);
+        true
+    }
+
+    /// Publishes the length left once `range` is drained from a collection
+    /// of `len`, ahead of the drain itself.
+    pub(crate) fn observe_drain(&mut self, len: usize, range: &impl RangeBounds<usize>) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+
+        self.observe_len(len - end.saturating_sub(start).min(len));
+    }
+
+    /// Records the change from the last capacity seen to `capacity`, if there
+    /// is one.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn observe_capacity(&mut self, capacity: usize, len: usize) {
+        if capacity == self.capacity {
+            return;
+        }
+
+        let previous_cap = mem::replace(&mut self.capacity, capacity);
+
+        self.published.capacity.store(capacity, Ordering::Relaxed);
+
+        match self.inline_capacity {
+            Some(inline_capacity) if previous_cap <= inline_capacity && capacity > previous_cap => {
+                self.record(ReportEvent::Spilled(previous_cap, capacity), len)
+            }
+            _ => self
+                .handle
+                .record_capacity_change(previous_cap, capacity, len),
+        }
+    }
+
+    /// Refuses to let a collection of `len` reserve room for `additional`
+    /// more elements if that would take it past its max capacity.
+    pub(crate) fn check_hard_cap(&self, len: usize, additional: usize) -> Result<(), Error> {
+        let requested = len.saturating_add(additional);
+
+        match Report::get(self.id()).max_capacity() {
+            Some(max_capacity) if requested > max_capacity => Err(Error::HardCapRejected {
+                id: self.id(),
+                max_capacity,
+                requested,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        let last = unregister(self.id(), &self.published);
+
+        Report::retire(self.id(), self.peak_len(), last);
+    }
+}
@@ -1,17 +1,16 @@
-use log::{info, warn};
-
 use std::any::type_name;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::vec::Vec as StdVec;
 
-use crate::sealed::Reportable;
-use crate::{Report, Uid};
+use crate::{Report, ReportEvent, Reportable, Tracker, Uid};
 
-pub struct Vec<T>(StdVec<T>, Uid);
+/// A `std::vec::Vec` which reports its capacity changes, along with its
+/// cached peak length.
+pub struct Vec<T>(StdVec<T>, Tracker);
 
 impl<T> Default for Vec<T> {
     fn default() -> Self {
-        Self(StdVec::new(), Report::new_line_item())
+        Self(StdVec::new(), Tracker::new("Vec", 0))
     }
 }
 
@@ -21,53 +20,76 @@ impl<T> Vec<T> {
         Self::default()
     }
 
+    #[track_caller]
     pub fn with_capacity(cap: usize) -> Self {
-        Self(StdVec::with_capacity(cap), Report::new_line_item())
+        let vec = StdVec::with_capacity(cap);
+        let tracker = Tracker::new("Vec", vec.capacity());
+        tracker.record(ReportEvent::MaxCapacitySet(cap), 0);
+
+        Self(vec, tracker)
     }
 
-    pub fn push(&mut self, item: T) {
-        // let current_cap = self.0.capacity();
-        if self.at_capacity() {
-            warn!("{} exceeded capacity {}", self, self.0.capacity());
-        }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 
-        self.0.push(item);
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 
-        info!("{} reached capacity {}", self, self.0.capacity());
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    #[track_caller]
+    pub fn push(&mut self, item: T) {
+        self.0.push(item);
+        self.observe_len();
+        self.1.observe_capacity(self.0.capacity(), self.0.len());
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self.0.pop()
+        let ret = self.0.pop();
+        self.observe_len();
+        ret
     }
 }
 
 // Extra Vec methods
 impl<T> Vec<T> {
     #[inline]
-    fn at_capacity(&self) -> bool {
-        self.0.len() == self.0.capacity()
+    fn observe_len(&mut self) {
+        self.1.observe_len(self.0.len());
     }
 
+    #[track_caller]
     pub fn set_name(&mut self, name: &str) {
-        let mut report = Report::get_mut(self.1);
-        report.instance_name = name.into();
+        self.1.set_name(name, self.0.len());
     }
 
+    #[track_caller]
     pub fn with_name(mut self, name: &str) -> Self {
         self.set_name(name);
         self
     }
+
+    /// Marks a point in the collection's history, such as the start of a
+    /// phase, which is shown inline in its event table.
+    #[track_caller]
+    pub fn annotate(&self, msg: &str) {
+        self.1.annotate(msg, self.0.len());
+    }
 }
 
 impl<T> Display for Vec<T> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
-        let name = &Report::get(self.1).instance_name;
+        let name = &Report::get(self.1.id()).instance_name;
         write!(fmt, "{name}: Vec<{}>", type_name::<T>())
     }
 }
 
 impl<T> Reportable for Vec<T> {
     fn id(&self) -> Uid {
-        self.1
+        self.1.id()
     }
 }